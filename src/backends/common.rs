@@ -0,0 +1,45 @@
+use std::str;
+
+use rustc_serialize::json::Json;
+use url::Url;
+
+use builders::common::ParseError;
+
+// the apis answer {"success": false} or {"success": 0} with an "error_message" when they refuse
+pub fn parse_json(body: &[u8]) -> Result<Json, ParseError> {
+    let s = try!(str::from_utf8(body).map_err(|e| ParseError::new(&format!("fail to parse json, reason: {}", e))));
+    let root = try!(Json::from_str(s).map_err(|e| ParseError::new(&format!("fail to parse json, reason: {}", e))));
+
+    let is_success = match root.find("success") {
+        Some(&Json::Boolean(false)) | Some(&Json::I64(0)) | Some(&Json::U64(0)) => false,
+        _ => true,
+    };
+
+    if is_success {
+        Ok(root)
+    } else {
+        let reason = text(&root, "error_message").unwrap_or(String::from("the api reports no success"));
+        Err(ParseError::new(&format!("fail to parse json, reason: {}", reason)))
+    }
+}
+
+// strings and numbers alike, as text
+pub fn text(json: &Json, key: &str) -> Option<String> {
+    match json.find(key) {
+        Some(&Json::String(ref s)) => Some(s.trim().to_string()),
+        Some(&Json::I64(n)) => Some(n.to_string()),
+        Some(&Json::U64(n)) => Some(n.to_string()),
+        Some(&Json::F64(n)) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+pub fn number<T: str::FromStr>(json: &Json, key: &str) -> Option<T> {
+    text(json, key).and_then(|x| x.replace(",", "").parse::<T>().ok())
+}
+
+pub fn query_value(url: &str, key: &str) -> Option<String> {
+    Url::parse(url).ok().and_then(|url| {
+        url.query_pairs().find(|&(ref k, _)| k == key).map(|(_, v)| v.into_owned())
+    })
+}
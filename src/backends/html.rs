@@ -0,0 +1,65 @@
+use ::kuchiki::traits::*;
+
+use model::{ListTopicItem, ShowItem};
+use builders::common::*;
+use builders::index::Index;
+use builders::show::Show;
+use backends::{Backend, ForumChannel, HKGOLDEN, DEFAULT_CHANNEL};
+
+// the desktop site, scraped with the builders
+pub struct HtmlBackend {}
+
+impl HtmlBackend {
+    pub fn new() -> Self {
+        HtmlBackend {}
+    }
+}
+
+impl Backend for HtmlBackend {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn forum(&self) -> &'static str {
+        HKGOLDEN
+    }
+
+    fn channels(&self) -> Vec<ForumChannel> {
+        vec![ForumChannel::new(HKGOLDEN, DEFAULT_CHANNEL, "高登 - 吹水台")]
+    }
+
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn content_type(&self) -> &'static str {
+        "text/html"
+    }
+
+    fn topics_url(&self, channel: &str, page: usize) -> String {
+        format!("http://archive.hkgolden.com/topics.aspx?type={channel}&page={page}",
+                channel = channel,
+                page = page)
+    }
+
+    fn show_url(&self, channel: &str, postid: &str, page: usize) -> String {
+        format!("http://forum1.hkgolden.com/view.aspx?type={channel}&message={postid}&page={page}",
+                channel = channel,
+                postid = postid,
+                page = page)
+    }
+
+    fn parse_topics(&self, body: &[u8], url: &str) -> Result<(Vec<ListTopicItem>, Vec<ParseError>), ParseError> {
+        let document = ::kuchiki::parse_html().from_utf8().one(body);
+        let mut builder = Index::new();
+        let items = try!(builder.build(&document, url));
+        Ok((items, builder.diagnostics().clone()))
+    }
+
+    fn parse_show(&self, body: &[u8], url: &str) -> Result<(ShowItem, Vec<ParseError>), ParseError> {
+        let document = ::kuchiki::parse_html().from_utf8().one(body);
+        let mut builder = Show::new();
+        let item = try!(builder.build(&document, url));
+        Ok((item, builder.diagnostics().clone()))
+    }
+}
@@ -0,0 +1,245 @@
+use rustc_serialize::json::Json;
+use regex::Regex;
+
+use model::*;
+use reply_model::*;
+use builders::common::*;
+use builders::show::parse_reply_body;
+use backends::{Backend, ForumChannel, HKGOLDEN, DEFAULT_CHANNEL};
+use backends::common::*;
+
+// as on the web pages
+const REPLIES_PER_PAGE: usize = 25;
+
+lazy_static! {
+    static ref DATE_RE: Regex = Regex::new(r"^/Date\((?P<ms>-?\d+)([+-]\d{4})?\)/$").expect("fail to parse date, reason: invalid regex");
+}
+
+// the api of the mobile apps, the same pages as json:
+//
+// topics: {"success": true, "data": {"list": [{"Message_ID", "Message_Title", "Author_ID", "Author_Name",
+//          "Last_Reply_Date": "/Date(1476870000000)/", "Total_Replies", "Message_Rating", "Total_Pages"}]}}
+// view:   {"success": true, "data": {"Message_ID", "Message_Title", "Author_ID", "Total_Replies", "Total_Pages",
+//          "Current_Pages", "messages": [{"Reply_ID", "Author_ID", "Author_Name", "Author_Gender",
+//          "Message_Body", "Message_Date"}]}}
+//
+// numbers may come as strings, so every field is read leniently
+pub struct JsonBackend {
+    api_url: String
+}
+
+impl JsonBackend {
+    pub fn new(api_url: &str) -> Self {
+        JsonBackend {
+            api_url: api_url.trim_right_matches('/').to_string()
+        }
+    }
+}
+
+impl Backend for JsonBackend {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn forum(&self) -> &'static str {
+        HKGOLDEN
+    }
+
+    fn channels(&self) -> Vec<ForumChannel> {
+        vec![ForumChannel::new(HKGOLDEN, DEFAULT_CHANNEL, "高登 - 吹水台")]
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn topics_url(&self, channel: &str, page: usize) -> String {
+        format!("{api_url}/topics.aspx?type={channel}&page={page}&returntype=json",
+                api_url = self.api_url,
+                channel = channel,
+                page = page)
+    }
+
+    fn show_url(&self, channel: &str, postid: &str, page: usize) -> String {
+        format!("{api_url}/view.aspx?type={channel}&message={postid}&page={page}&returntype=json",
+                api_url = self.api_url,
+                channel = channel,
+                postid = postid,
+                page = page)
+    }
+
+    fn parse_topics(&self, body: &[u8], url: &str) -> Result<(Vec<ListTopicItem>, Vec<ParseError>), ParseError> {
+        let root = try!(parse_json(body));
+        let channel = query_value(url, "type").unwrap_or(String::from(DEFAULT_CHANNEL));
+
+        let list = match root.find_path(&["data", "list"]).and_then(|x| x.as_array()) {
+            Some(list) => list,
+            None => return Err(ParseError::new("fail to parse list topics item, reason: 'data.list' not found")),
+        };
+
+        let mut result = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        // a broken topic is left out, the rest of the list is still worth showing
+        for (index, topic) in list.iter().enumerate() {
+            match list_topic_item(topic, &channel) {
+                Ok(item) => result.push(item),
+                Err(e) => diagnostics.push(e.with_index(index)),
+            }
+        }
+
+        if result.is_empty() && !diagnostics.is_empty() {
+            return Err(diagnostics[0].clone());
+        }
+
+        Ok((result, diagnostics))
+    }
+
+    fn parse_show(&self, body: &[u8], url: &str) -> Result<(ShowItem, Vec<ParseError>), ParseError> {
+        let root = try!(parse_json(body));
+
+        let data = match root.find("data") {
+            Some(data) => data,
+            None => return Err(ParseError::new("fail to parse show item, reason: 'data' not found")),
+        };
+
+        let message = match text(data, "Message_ID").or_else(|| query_value(url, "message")) {
+            Some(message) => message,
+            None => return Err(ParseError::new("fail to parse show item, reason: 'Message_ID' not found")),
+        };
+
+        let page = number::<usize>(data, "Current_Pages").or_else(|| query_value(url, "page").and_then(|x| x.parse().ok())).unwrap_or(1);
+        let max_page = number::<usize>(data, "Total_Pages").unwrap_or(page);
+
+        let messages = match data.find("messages").and_then(|x| x.as_array()) {
+            Some(messages) => messages,
+            None => return Err(ParseError::new("fail to parse show item, reason: 'messages' not found")),
+        };
+
+        let mut replies = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        // one odd reply should not cost the whole page, it is shown as a placeholder instead
+        for (index, reply) in messages.iter().enumerate() {
+            // the api numbers no floors, every page but the last is full
+            let floor = Some(page.saturating_sub(1) * REPLIES_PER_PAGE + index + 1);
+            match show_reply_item(reply, floor) {
+                Ok(item) => replies.push(item),
+                Err(e) => {
+                    let e = e.with_index(index);
+                    replies.push(ShowReplyItem {
+                        userid: text(reply, "Author_ID").unwrap_or_default(),
+                        username: text(reply, "Author_Name").unwrap_or_default(),
+                        body: vec![NodeType::Text(TextNode { data: reply.to_string() })],
+                        floor: floor,
+                        diagnostic: Some(e.clone()),
+                        ..Default::default()
+                    });
+                    diagnostics.push(e);
+                }
+            }
+        }
+
+        // the thread names its author, page 1 also starts with the author's reply
+        let author_userid = text(data, "Author_ID").or_else(|| {
+            if page == 1 {
+                replies.iter().find(|x| x.diagnostic.is_none()).map(|x| x.userid.clone())
+            } else {
+                None
+            }
+        });
+
+        let mut show_item = ShowItem {
+            url_query: UrlQueryItem {
+                forum: String::from(HKGOLDEN),
+                channel: query_value(url, "type").unwrap_or(String::from(DEFAULT_CHANNEL)),
+                message: message
+            },
+            title: text(data, "Message_Title").unwrap_or_default(),
+            reply_count: number::<usize>(data, "Total_Replies"),
+            page: page,
+            max_page: max_page,
+            replies: replies,
+            author_userid: None
+        };
+        show_item.set_author(author_userid);
+
+        Ok((show_item, diagnostics))
+    }
+}
+
+fn list_topic_item(topic: &Json, channel: &str) -> Result<ListTopicItem, ParseError> {
+    let message = match text(topic, "Message_ID") {
+        Some(message) => message,
+        None => return Err(ParseError::new("fail to parse list topic item, reason: 'Message_ID' not found")),
+    };
+
+    Ok(ListTopicItem {
+        title: ListTopicTitleItem {
+            url: format!("http://forum1.hkgolden.com/view.aspx?type={}&message={}", channel, message),
+            url_query: UrlQueryItem {
+                forum: String::from(HKGOLDEN),
+                channel: channel.to_string(),
+                message: message
+            },
+            text: text(topic, "Message_Title").unwrap_or_default(),
+            num_of_pages: number::<usize>(topic, "Total_Pages").unwrap_or(1)
+        },
+        author: ListTopicAuthorItem {
+            url: Default::default(),
+            name: text(topic, "Author_Name").unwrap_or_default()
+        },
+        last_replied_at: timestamp(topic, "Last_Reply_Date"),
+        reply_count: number::<usize>(topic, "Total_Replies"),
+        rating: number::<i32>(topic, "Message_Rating"),
+    })
+}
+
+fn show_reply_item(reply: &Json, floor: Option<usize>) -> Result<ShowReplyItem, ParseError> {
+    let userid = match text(reply, "Author_ID") {
+        Some(userid) => userid,
+        None => return Err(ParseError::new("fail to parse show reply item, reason: 'Author_ID' not found")),
+    };
+
+    let content = match text(reply, "Message_Body") {
+        Some(content) => content,
+        None => return Err(ParseError::new("fail to parse show reply item, reason: 'Message_Body' not found")),
+    };
+
+    let gender = match text(reply, "Author_Gender").map(|x| x.to_lowercase()) {
+        Some(ref x) if x == "m" || x == "male" => Gender::Male,
+        Some(ref x) if x == "f" || x == "female" => Gender::Female,
+        _ => Gender::Unknown,
+    };
+
+    Ok(ShowReplyItem {
+        userid: userid,
+        username: text(reply, "Author_Name").unwrap_or_default(),
+        body: parse_reply_body(&content, "http://forum1.hkgolden.com/view.aspx"),
+        content: content,
+        published_at: timestamp(reply, "Message_Date"),
+        floor: floor,
+        reply_id: text(reply, "Reply_ID"),
+        gender: gender,
+        is_op: false,
+        is_moderator: false,
+        diagnostic: None,
+    })
+}
+
+// "/Date(1476870000000)/" or "/Date(1476870000000+0800)/", milliseconds since the epoch
+fn timestamp(json: &Json, key: &str) -> Timestamp {
+    let s = text(json, key).unwrap_or_default();
+    let seconds = DATE_RE.captures(&s)
+                    .and_then(|cap| cap.name("ms"))
+                    .and_then(|x| x.parse::<i64>().ok())
+                    .map(|x| x / 1000);
+    Timestamp {
+        text: s,
+        seconds: seconds
+    }
+}
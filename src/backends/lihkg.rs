@@ -0,0 +1,284 @@
+use rustc_serialize::json::Json;
+
+use model::*;
+use reply_model::*;
+use builders::common::*;
+use builders::show::parse_reply_body;
+use backends::{Backend, ForumChannel, LIHKG};
+use backends::common::*;
+
+// lihkg only has an api, the shapes it answers with:
+//
+// category: {"success": 1, "response": {"items": [{"thread_id", "cat_id", "title", "user_id", "user_nickname",
+//            "no_of_reply", "like_count", "dislike_count", "last_reply_time": 1500000000, "total_page"}]}}
+// thread:   {"success": 1, "response": {"thread_id", "cat_id", "title", "user_id", "no_of_reply", "page",
+//            "total_page", "item_data": [{"post_id", "msg_num", "user_nickname", "user_gender", "msg",
+//            "reply_time", "user": {"user_id", "level"}}]}}
+// refusals: {"success": 0, "error_code": 100, "error_message": "..."}
+pub struct LihkgBackend {
+    base_url: String
+}
+
+// level of the site's own accounts
+const ADMIN_LEVEL: &'static str = "999";
+
+impl LihkgBackend {
+    pub fn new(base_url: &str) -> Self {
+        LihkgBackend {
+            base_url: base_url.trim_right_matches('/').to_string()
+        }
+    }
+
+    fn thread_url(&self, postid: &str, page: usize) -> String {
+        format!("{base_url}/thread/{postid}/page/{page}", base_url = self.base_url, postid = postid, page = page)
+    }
+}
+
+impl Backend for LihkgBackend {
+    fn name(&self) -> &'static str {
+        "lihkg"
+    }
+
+    fn forum(&self) -> &'static str {
+        LIHKG
+    }
+
+    fn channels(&self) -> Vec<ForumChannel> {
+        vec![
+            ForumChannel::new(LIHKG, "1", "LIHKG - 吹水台"),
+            ForumChannel::new(LIHKG, "2", "LIHKG - 熱門台"),
+            ForumChannel::new(LIHKG, "5", "LIHKG - 時事台"),
+        ]
+    }
+
+    fn extension(&self) -> &'static str {
+        "lihkg.json"
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn topics_url(&self, channel: &str, page: usize) -> String {
+        format!("{base_url}/api_v2/thread/category?cat_id={channel}&page={page}&count=60",
+                base_url = self.base_url,
+                channel = channel,
+                page = page)
+    }
+
+    fn show_url(&self, _channel: &str, postid: &str, page: usize) -> String {
+        format!("{base_url}/api_v2/thread/{postid}/page/{page}?order=reply_time",
+                base_url = self.base_url,
+                postid = postid,
+                page = page)
+    }
+
+    fn parse_topics(&self, body: &[u8], url: &str) -> Result<(Vec<ListTopicItem>, Vec<ParseError>), ParseError> {
+        let root = try!(parse_json(body));
+
+        let items = match root.find_path(&["response", "items"]).and_then(|x| x.as_array()) {
+            Some(items) => items,
+            None => return Err(ParseError::new("fail to parse list topics item, reason: 'response.items' not found")),
+        };
+
+        let mut result = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        // a broken topic is left out, the rest of the list is still worth showing
+        for (index, topic) in items.iter().enumerate() {
+            match self.list_topic_item(topic, url) {
+                Ok(item) => result.push(item),
+                Err(e) => diagnostics.push(e.with_index(index)),
+            }
+        }
+
+        if result.is_empty() && !diagnostics.is_empty() {
+            return Err(diagnostics[0].clone());
+        }
+
+        Ok((result, diagnostics))
+    }
+
+    fn parse_show(&self, body: &[u8], url: &str) -> Result<(ShowItem, Vec<ParseError>), ParseError> {
+        let root = try!(parse_json(body));
+
+        let response = match root.find("response") {
+            Some(response) => response,
+            None => return Err(ParseError::new("fail to parse show item, reason: 'response' not found")),
+        };
+
+        let message = match text(response, "thread_id") {
+            Some(message) => message,
+            None => return Err(ParseError::new("fail to parse show item, reason: 'thread_id' not found")),
+        };
+
+        let page = number::<usize>(response, "page").unwrap_or(1);
+        let max_page = number::<usize>(response, "total_page").unwrap_or(page);
+        let base_url = self.thread_url(&message, page);
+
+        let messages = match response.find("item_data").and_then(|x| x.as_array()) {
+            Some(messages) => messages,
+            None => return Err(ParseError::new("fail to parse show item, reason: 'item_data' not found")),
+        };
+
+        let mut replies = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        // one odd reply should not cost the whole page, it is shown as a placeholder instead
+        for (index, reply) in messages.iter().enumerate() {
+            match show_reply_item(reply, &base_url) {
+                Ok(item) => replies.push(item),
+                Err(e) => {
+                    let e = e.with_index(index);
+                    replies.push(ShowReplyItem {
+                        userid: reply_userid(reply).unwrap_or_default(),
+                        username: text(reply, "user_nickname").unwrap_or_default(),
+                        body: vec![NodeType::Text(TextNode { data: reply.to_string() })],
+                        diagnostic: Some(e.clone()),
+                        ..Default::default()
+                    });
+                    diagnostics.push(e);
+                }
+            }
+        }
+
+        let mut show_item = ShowItem {
+            url_query: UrlQueryItem {
+                forum: String::from(LIHKG),
+                channel: text(response, "cat_id").unwrap_or_default(),
+                message: message
+            },
+            title: text(response, "title").unwrap_or_default(),
+            reply_count: number::<usize>(response, "no_of_reply"),
+            page: page,
+            max_page: max_page,
+            replies: replies,
+            author_userid: None
+        };
+        // every page names the author of the thread
+        show_item.set_author(text(response, "user_id"));
+
+        Ok((show_item, diagnostics))
+    }
+}
+
+impl LihkgBackend {
+    fn list_topic_item(&self, topic: &Json, url: &str) -> Result<ListTopicItem, ParseError> {
+        let message = match text(topic, "thread_id") {
+            Some(message) => message,
+            None => return Err(ParseError::new("fail to parse list topic item, reason: 'thread_id' not found")),
+        };
+
+        let channel = text(topic, "cat_id").or_else(|| query_value(url, "cat_id")).unwrap_or_default();
+
+        // likes less dislikes, the closest to hkgolden's rating
+        let rating = match (number::<i32>(topic, "like_count"), number::<i32>(topic, "dislike_count")) {
+            (Some(like), Some(dislike)) => Some(like - dislike),
+            (Some(like), None) => Some(like),
+            _ => None,
+        };
+
+        Ok(ListTopicItem {
+            title: ListTopicTitleItem {
+                url: self.thread_url(&message, 1),
+                url_query: UrlQueryItem {
+                    forum: String::from(LIHKG),
+                    channel: channel,
+                    message: message
+                },
+                text: text(topic, "title").unwrap_or_default(),
+                num_of_pages: number::<usize>(topic, "total_page").unwrap_or(1)
+            },
+            author: ListTopicAuthorItem {
+                url: Default::default(),
+                name: text(topic, "user_nickname").unwrap_or_default()
+            },
+            last_replied_at: timestamp(topic, "last_reply_time"),
+            reply_count: number::<usize>(topic, "no_of_reply"),
+            rating: rating,
+        })
+    }
+}
+
+fn show_reply_item(reply: &Json, base_url: &str) -> Result<ShowReplyItem, ParseError> {
+    let userid = match reply_userid(reply) {
+        Some(userid) => userid,
+        None => return Err(ParseError::new("fail to parse show reply item, reason: 'user.user_id' not found")),
+    };
+
+    let content = match text(reply, "msg") {
+        Some(content) => content,
+        None => return Err(ParseError::new("fail to parse show reply item, reason: 'msg' not found")),
+    };
+
+    let gender = match text(reply, "user_gender").map(|x| x.to_uppercase()) {
+        Some(ref x) if x == "M" => Gender::Male,
+        Some(ref x) if x == "F" => Gender::Female,
+        _ => Gender::Unknown,
+    };
+
+    let is_moderator = reply.find("user").and_then(|x| text(x, "level")).map_or(false, |x| x == ADMIN_LEVEL);
+
+    let mut body = parse_reply_body(&content, base_url);
+    name_images(&mut body);
+
+    Ok(ShowReplyItem {
+        userid: userid,
+        username: text(reply, "user_nickname").unwrap_or_default(),
+        content: content,
+        body: body,
+        published_at: timestamp(reply, "reply_time"),
+        floor: number::<usize>(reply, "msg_num"),
+        reply_id: text(reply, "post_id"),
+        gender: gender,
+        is_op: false,
+        is_moderator: is_moderator,
+        diagnostic: None,
+    })
+}
+
+fn reply_userid(reply: &Json) -> Option<String> {
+    reply.find("user").and_then(|x| text(x, "user_id")).or_else(|| text(reply, "user_id"))
+}
+
+// unix seconds
+fn timestamp(json: &Json, key: &str) -> Timestamp {
+    let s = text(json, key).unwrap_or_default();
+    Timestamp {
+        seconds: s.parse::<i64>().ok(),
+        text: s
+    }
+}
+
+// stickers come without alt text, "/assets/faces/normal/smile.gif" becomes "#smile#" like an hkgolden icon,
+// other images are marked as [img] pictures so they are downloaded
+fn name_images(nodes: &mut Vec<NodeType>) {
+    for node in nodes.iter_mut() {
+        match *node {
+            NodeType::Image(ref mut n) if n.alt.is_empty() => {
+                n.alt = if n.data.contains("/assets/faces/") {
+                    let file_name = n.data.rsplit('/').next().unwrap_or("");
+                    format!("#{}#", file_name.split('.').next().unwrap_or(""))
+                } else {
+                    format!("[img]{}[/img]", n.data)
+                };
+            }
+            NodeType::Span(ref mut n) => name_images(&mut n.data),
+            NodeType::Link(ref mut n) => name_images(&mut n.data),
+            NodeType::BlockQuote(ref mut n) => name_images(&mut n.data),
+            NodeType::List(ref mut n) => {
+                for item in n.items.iter_mut() {
+                    name_images(item);
+                }
+            }
+            NodeType::Table(ref mut n) => {
+                for row in n.rows.iter_mut() {
+                    for cell in row.iter_mut() {
+                        name_images(&mut cell.data);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,127 @@
+pub mod common;
+pub mod html;
+pub mod json;
+pub mod lihkg;
+
+use std::sync::Arc;
+
+use model::{ListTopicItem, ShowItem};
+use builders::common::ParseError;
+use config::BackendConfig;
+
+pub const HKGOLDEN: &'static str = "hkgolden";
+pub const LIHKG: &'static str = "lihkg";
+
+// the hkgolden board the client started with
+pub const DEFAULT_CHANNEL: &'static str = "BW";
+
+// a board of a forum, as offered in the channel picker
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+#[derive(PartialEq)]
+pub struct ForumChannel {
+    pub forum: String,
+    pub channel: String,
+    pub name: String
+}
+
+impl ForumChannel {
+    pub fn new(forum: &str, channel: &str, name: &str) -> Self {
+        ForumChannel {
+            forum: forum.to_string(),
+            channel: channel.to_string(),
+            name: name.to_string()
+        }
+    }
+}
+
+// a surface of the forum, it knows where the pages are and how to read them,
+// fetching and caching them stays with the resources
+pub trait Backend: Send + Sync {
+    // names the backend in the config and in logs
+    fn name(&self) -> &'static str;
+
+    // the forum it reads, hkgolden has more than one surface
+    fn forum(&self) -> &'static str;
+
+    // the boards it offers, the first one is shown at startup
+    fn channels(&self) -> Vec<ForumChannel>;
+
+    // file extension of the cached pages, pages of different backends are kept apart
+    fn extension(&self) -> &'static str;
+
+    fn content_type(&self) -> &'static str;
+
+    fn topics_url(&self, channel: &str, page: usize) -> String;
+
+    fn show_url(&self, channel: &str, postid: &str, page: usize) -> String;
+
+    // the parts of the page that were skipped come back next to the result
+    fn parse_topics(&self, body: &[u8], url: &str) -> Result<(Vec<ListTopicItem>, Vec<ParseError>), ParseError>;
+
+    fn parse_show(&self, body: &[u8], url: &str) -> Result<(ShowItem, Vec<ParseError>), ParseError>;
+}
+
+// one backend per forum, the first one is the default
+#[derive(Clone)]
+pub struct Backends {
+    backends: Vec<Arc<dyn Backend>>
+}
+
+impl Backends {
+    pub fn new(backends: Vec<Arc<dyn Backend>>) -> Self {
+        Backends {
+            backends: backends
+        }
+    }
+
+    // an empty or unknown forum gets the default backend
+    pub fn get(&self, forum: &str) -> Arc<dyn Backend> {
+        self.backends.iter()
+                     .find(|x| x.forum() == forum)
+                     .unwrap_or(&self.backends[0])
+                     .clone()
+    }
+
+    pub fn channels(&self) -> Vec<ForumChannel> {
+        self.backends.iter().flat_map(|x| x.channels().into_iter()).collect()
+    }
+
+    pub fn default_channel(&self) -> ForumChannel {
+        self.channels().into_iter().next().unwrap_or_default()
+    }
+
+    // an empty channel is the first board of the forum
+    pub fn channel_or_default(&self, forum: &str, channel: &str) -> String {
+        if channel.is_empty() {
+            self.get(forum).channels().into_iter().next().map(|x| x.channel).unwrap_or_default()
+        } else {
+            channel.to_string()
+        }
+    }
+}
+
+// tells the cached topic lists of each board apart, the default hkgolden board keeps the plain extension
+pub fn snapshot_suffix(backend: &dyn Backend, channel: &str) -> String {
+    if backend.forum() == HKGOLDEN && channel == DEFAULT_CHANNEL {
+        backend.extension().to_string()
+    } else {
+        format!("{}.{}", channel, backend.extension())
+    }
+}
+
+pub fn from_config(config: &BackendConfig) -> Backends {
+    let hkgolden: Arc<dyn Backend> = match config.name().as_str() {
+        "html" => Arc::new(html::HtmlBackend::new()),
+        "json" => Arc::new(json::JsonBackend::new(&config.api_url())),
+        name => {
+            error!("backend - unknown backend {}, use html", name);
+            Arc::new(html::HtmlBackend::new())
+        }
+    };
+
+    let lihkg: Arc<dyn Backend> = Arc::new(lihkg::LihkgBackend::new(&config.lihkg_url()));
+
+    Backends::new(vec![hkgolden, lihkg])
+}
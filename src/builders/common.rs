@@ -0,0 +1,86 @@
+use std::fmt;
+use std::io::Cursor;
+
+use kuchiki::NodeRef;
+
+// enough html to recognise the offending markup in a log line
+const SNIPPET_LENGTH: usize = 160;
+
+// what failed to parse and where
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct ParseError {
+    pub message: String,
+    // the css selector that matched nothing
+    pub selector: Option<String>,
+    // reply index on a thread page, row on the topic list
+    pub index: Option<usize>,
+    pub snippet: Option<String>
+}
+
+impl ParseError {
+    pub fn new(message: &str) -> Self {
+        ParseError {
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_selector(mut self, selector: &str) -> Self {
+        self.selector = Some(selector.to_string());
+        self
+    }
+
+    pub fn with_index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    pub fn with_snippet(mut self, node: &NodeRef) -> Self {
+        self.snippet = Some(snippet(node));
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{}", self.message));
+        if let Some(ref selector) = self.selector {
+            try!(write!(f, ", selector: '{}'", selector));
+        }
+        if let Some(index) = self.index {
+            try!(write!(f, ", at: #{}", index));
+        }
+        if let Some(ref snippet) = self.snippet {
+            try!(write!(f, ", html: {}", snippet));
+        }
+        Ok(())
+    }
+}
+
+// every page builder turns a parsed document into a model
+pub trait PageBuilder {
+    type Item;
+    fn build(&mut self, document: &NodeRef, url: &str) -> Result<Self::Item, ParseError>;
+    // rows or replies the last build could not parse, the page is built without them
+    fn diagnostics(&self) -> &Vec<ParseError>;
+}
+
+pub fn snippet(node: &NodeRef) -> String {
+    let mut buff = Cursor::new(Vec::new());
+    match node.serialize(&mut buff) {
+        Ok(_) => {}
+        Err(e) => return format!("<{}>", e),
+    }
+    let html = String::from_utf8_lossy(&buff.into_inner()).into_owned();
+    let html = html.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if html.chars().count() > SNIPPET_LENGTH {
+        format!("{}...", html.chars().take(SNIPPET_LENGTH).collect::<String>())
+    } else {
+        html
+    }
+}
@@ -9,48 +9,73 @@ use model::ListTopicItem;
 use model::ListTopicTitleItem;
 use model::ListTopicAuthorItem;
 use model::UrlQueryItem;
+use model::Timestamp;
+use backends::HKGOLDEN;
+use utility::time::FORUM_TIME_FORMAT;
+use builders::common::*;
 
 use regex::Regex;
 use url::Url;
 use std::collections::HashMap;
 
 #[derive(Clone)]
-pub struct Index {}
+pub struct Index {
+    diagnostics: Vec<ParseError>
+}
 
 impl Index {
     pub fn new() -> Self {
-        Index {}
+        Index {
+            diagnostics: Vec::new()
+        }
     }
-    pub fn build(&mut self, document: &NodeRef) -> Result<Vec<ListTopicItem>, &'static str> {
+}
+
+impl PageBuilder for Index {
+    type Item = Vec<ListTopicItem>;
+
+    fn build(&mut self, document: &NodeRef, _url: &str) -> Result<Vec<ListTopicItem>, ParseError> {
+
+        self.diagnostics.clear();
 
         let trs_option = document.select(".Topic_ListPanel tr[id]");
 
         if trs_option.is_err() {
-            return Err("fail to parse list topics item, reaons: trs_option not found");
+            return Err(ParseError::new("fail to parse list topics item, reaons: trs_option not found")
+                                  .with_selector(".Topic_ListPanel tr[id]"));
         }
 
         let trs = trs_option.unwrap();
 
-        let list_topics = trs.enumerate().map(list_topic_items_handler).collect::<Vec<_>>();
+        let mut result = Vec::new();
 
-        let err_list_topics_option = list_topics.iter().filter(|x| x.is_err()).next();
-
-        if err_list_topics_option.is_some() {
-            return Err(&"fail to parse list topics items, reason: 'error list topic item' was found");
+        // a broken row is left out, the rest of the list is still worth showing
+        for list_topic in trs.enumerate().map(list_topic_items_handler) {
+            match list_topic {
+                Ok(item) => result.push(item),
+                Err(e) => self.diagnostics.push(e),
+            }
         }
 
-        let result = list_topics.iter().map(|x| x.clone().unwrap()).collect::<Vec<_>>();
+        if result.is_empty() && !self.diagnostics.is_empty() {
+            return Err(self.diagnostics[0].clone());
+        }
 
         Ok(result)
     }
+
+    fn diagnostics(&self) -> &Vec<ParseError> {
+        &self.diagnostics
+    }
 }
 
-fn list_topic_items_handler((index, tr): (usize, ::kuchiki::NodeDataRef<::kuchiki::ElementData>)) -> Result<ListTopicItem, &'static str> {
+fn list_topic_items_handler((index, tr): (usize, ::kuchiki::NodeDataRef<::kuchiki::ElementData>)) -> Result<ListTopicItem, ParseError> {
+    let row_error = |message: &str| ParseError::new(message).with_index(index).with_snippet(tr.as_node());
 
     let items_option = tr.as_node().select("td");
 
     if items_option.is_err() {
-        return Err("fail to parse list topic items, reason: items_option not found");
+        return Err(row_error("fail to parse list topic items, reason: items_option not found").with_selector("td"));
     }
 
     let items = items_option.unwrap();
@@ -79,7 +104,7 @@ fn list_topic_items_handler((index, tr): (usize, ::kuchiki::NodeDataRef<::kuchik
 
                     if count < 2 {
                         error!("length of map is invalid. length: {}", count);
-                        return Err(&"length of map is invalid.");
+                        return Err(row_error("length of map is invalid."));
                     }
 
                     let mut map_enumerator = map.iter().enumerate();
@@ -100,25 +125,24 @@ fn list_topic_items_handler((index, tr): (usize, ::kuchiki::NodeDataRef<::kuchik
                     };
 
                     if date_option.is_none() {
-                        return Err("fail to parse list topic items, reason: date_option not found");
+                        return Err(row_error("fail to parse list topic items, reason: date_option not found"));
                     }
 
                     if time_option.is_none() {
-                        return Err("fail to parse list topic items, reason: time_option not found");
+                        return Err(row_error("fail to parse list topic items, reason: time_option not found"));
                     }
 
                     (date_option.unwrap().clone(), time_option.unwrap().clone())
                 };
-                result.last_replied_date = date;
-                result.last_replied_time = time;
+                result.last_replied_at = Timestamp::from_hkt(&format!("{} {}", date, time), FORUM_TIME_FORMAT);
             }
             4 => {
                 let text = item.text_contents().trim().to_string();
-                result.reply_count = text
+                result.reply_count = text.replace(",", "").parse::<usize>().ok()
             }
             5 => {
                 let text = item.text_contents().trim().to_string();
-                result.rating = text
+                result.rating = text.replace(",", "").parse::<i32>().ok()
             }
             _ => {}
         }
@@ -276,6 +300,7 @@ fn parse_url_query_item(url_str: &str) -> Result<UrlQueryItem, &'static str> {
     };
 
     Ok(UrlQueryItem {
+           forum: String::from(HKGOLDEN),
            channel: String::from(channel),
            message: String::from(message),
        })
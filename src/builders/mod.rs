@@ -1,2 +1,6 @@
+pub mod common;
 pub mod index;
 pub mod show;
+
+// bump whenever the builders or the models change, cached models of older versions are parsed again
+pub const PARSER_VERSION: u32 = 10;
@@ -4,25 +4,49 @@ use kuchiki::NodeRef;
 use kuchiki::NodeDataRef;
 use kuchiki::NodeData;
 use kuchiki::ElementData;
+use kuchiki::iter::NodeIterator;
+use kuchiki::traits::*;
 
 use model::ShowItem;
 use model::ShowReplyItem;
 use model::UrlQueryItem;
+use model::Timestamp;
+use model::Gender;
+use backends::HKGOLDEN;
+use utility::time::FORUM_TIME_FORMAT;
+use utility::color::parse_css_color;
 use reply_model::*;
+use builders::common::*;
 
 use regex::Regex;
 use url::Url;
 use std::collections::HashMap;
 
+// matched once per reply, so built once
+lazy_static! {
+    static ref FLOOR_RE: Regex = Regex::new(r"^#\s*(?P<floor>\d+)$").expect("fail to parse floor, reason: invalid regex");
+    static ref REPLY_ID_RE: Regex = Regex::new(r"[?&]rid=(?P<rid>\d+)").expect("fail to parse reply id, reason: invalid regex");
+}
+
 #[derive(Clone)]
-pub struct Show { }
+pub struct Show {
+    diagnostics: Vec<ParseError>
+}
 
 impl Show {
     pub fn new() -> Self {
-        Show {}
+        Show {
+            diagnostics: Vec::new()
+        }
     }
+}
+
+impl PageBuilder for Show {
+    type Item = ShowItem;
 
-    pub fn build(&mut self, document: &NodeRef,  url: &str) -> Result<ShowItem, &'static str> {
+    fn build(&mut self, document: &NodeRef,  url: &str) -> Result<ShowItem, ParseError> {
+
+        self.diagnostics.clear();
 
         let url_query = match self.parse_url_query_item(&url) {
             Ok(url_query) => url_query,
@@ -40,40 +64,12 @@ impl Show {
             }
         };
 
-        let (page, max_page) = {
-
-            let page_select_option = document.select("select[name='page']").ok().map_or(None, |x| x.last());
-
-            if page_select_option.is_none() {
-                return Err("fail to build page and max_page, reason: 'page_select' not found");
-            }
-
-            let page_select = page_select_option.unwrap();
-
-            let page_str_option = page_select.as_node().select("option[selected='selected']").ok().map_or(None, |mut x| x.next());
-
-            if page_str_option.is_none() {
-                return Err("fail to build page and max_page, reason: 'page_str' not found");
-            }
-
-            let page_str = page_str_option.unwrap();
-
-            let max_page_str_option = page_select.as_node().select("option").ok().map_or(None, |x| x.last());
-
-            if max_page_str_option.is_none() {
-                return Err("fail to build page and max_page, reason: 'max_page_str' not found");
+        let (page, max_page) = match self.parse_page_and_max_page(&document) {
+            Ok((page, max_page)) => (page, max_page),
+            Err(e) =>  {
+                error!("{}", e);
+                return Err(e)
             }
-
-            let max_page_str = max_page_str_option.unwrap();
-
-            let page = page_str.text_contents().trim().to_string().parse::<usize>().unwrap_or(0);
-            let max_page = max_page_str.text_contents()
-                                       .trim()
-                                       .to_string()
-                                       .parse::<usize>()
-                                       .unwrap_or(0);
-
-            (page, max_page)
         };
 
         let replies = {
@@ -81,41 +77,94 @@ impl Show {
 
             if replies_option.is_err() {
                 let e = replies_option.err().unwrap();
-                error!("{:?}", e);
+                error!("{}", e);
                 return Err(e);
             }
 
             replies_option.unwrap()
         };
 
-        let show_item = ShowItem {
+        // the first reply of the thread is the author's
+        let author_userid = if page == 1 {
+            replies.iter().find(|x| x.diagnostic.is_none()).map(|x| x.userid.clone())
+        } else {
+            None
+        };
+
+        let mut show_item = ShowItem {
             url_query: url_query,
             replies: replies,
             page: page,
             max_page: max_page,
-            reply_count: String::from(reply_count),
+            reply_count: reply_count,
             title: String::from(title),
+            author_userid: None,
         };
+        show_item.set_author(author_userid);
 
         Ok(show_item)
     }
 
+    fn diagnostics(&self) -> &Vec<ParseError> {
+        &self.diagnostics
+    }
 }
 
 
 impl Show {
 
-    fn parse_url_query_item(&self, url_str: &str) -> Result<UrlQueryItem, &'static str> {
+    pub fn parse_page_and_max_page(&self, document: &NodeRef) -> Result<(usize, usize), ParseError> {
+
+        let page_select_option = document.select("select[name='page']").ok().map_or(None, |x| x.last());
+
+        if page_select_option.is_none() {
+            return Err(ParseError::new("fail to build page and max_page, reason: 'page_select' not found")
+                                  .with_selector("select[name='page']"));
+        }
+
+        let page_select = page_select_option.unwrap();
+
+        let page_str_option = page_select.as_node().select("option[selected='selected']").ok().map_or(None, |mut x| x.next());
+
+        if page_str_option.is_none() {
+            return Err(ParseError::new("fail to build page and max_page, reason: 'page_str' not found")
+                                  .with_selector("option[selected='selected']")
+                                  .with_snippet(page_select.as_node()));
+        }
+
+        let page_str = page_str_option.unwrap();
+
+        let max_page_str_option = page_select.as_node().select("option").ok().map_or(None, |x| x.last());
+
+        if max_page_str_option.is_none() {
+            return Err(ParseError::new("fail to build page and max_page, reason: 'max_page_str' not found")
+                                  .with_selector("option")
+                                  .with_snippet(page_select.as_node()));
+        }
+
+        let max_page_str = max_page_str_option.unwrap();
+
+        let page = page_str.text_contents().trim().to_string().parse::<usize>().unwrap_or(0);
+        let max_page = max_page_str.text_contents()
+                                   .trim()
+                                   .to_string()
+                                   .parse::<usize>()
+                                   .unwrap_or(0);
+
+        Ok((page, max_page))
+    }
+
+    fn parse_url_query_item(&self, url_str: &str) -> Result<UrlQueryItem, ParseError> {
 
         let url_option = Url::parse(&url_str);
         if url_option.is_err() {
-            return Err("fail to parse url query item, reason: invalid url");
+            return Err(ParseError::new(&format!("fail to parse url query item, reason: invalid url '{}'", url_str)));
         }
         let url = url_option.unwrap();
 
         let query_option = url.query();
         if query_option.is_none() {
-            return Err("fail to parse url query item, reason: invalid url query");
+            return Err(ParseError::new(&format!("fail to parse url query item, reason: invalid url query '{}'", url_str)));
         }
         let query = query_option.unwrap();
 
@@ -134,17 +183,17 @@ impl Show {
             let count = map.len();
             if count < 2 {
                 error!("length of map is invalid. length: {}", count);
-                return Err(&"length of map is invalid.");
+                return Err(ParseError::new(&format!("length of map is invalid. url: '{}'", url_str)));
             }
 
             let type_option = map.get("type");
             if type_option.is_none() {
-                return Err(&"fail to parse url query item, reason: can not get value of 'type' attribute");
+                return Err(ParseError::new("fail to parse url query item, reason: can not get value of 'type' attribute"));
             }
 
             let message_option = map.get("message");
             if message_option.is_none() {
-                return Err(&"fail to parse url query item, reason: can not get value of 'message' attribute");
+                return Err(ParseError::new("fail to parse url query item, reason: can not get value of 'message' attribute"));
             }
 
             (
@@ -155,13 +204,14 @@ impl Show {
 
         Ok(
             UrlQueryItem {
+                forum: String::from(HKGOLDEN),
                 channel: String::from(channel),
                 message: String::from(message)
             }
         )
     }
 
-    fn parse_title_and_reply_count (&self, document: &NodeRef,  url: &str) -> Result<(String, String), &'static str> {
+    fn parse_title_and_reply_count (&self, document: &NodeRef,  url: &str) -> Result<(String, Option<usize>), ParseError> {
 
         return match document.select(".repliers tr") {
             Ok(mut trs) => {
@@ -171,7 +221,9 @@ impl Show {
                         let repliers_header_option = repliers_tr.as_node().select(".repliers_header").ok().map_or(None, |x| x.last() );
 
                         if repliers_header_option.is_none() {
-                            return Err("fail to build title and reply_count, reason: 'repliers_header' not found");
+                            return Err(ParseError::new("fail to build title and reply_count, reason: 'repliers_header' not found")
+                                                  .with_selector(".repliers_header")
+                                                  .with_snippet(repliers_tr.as_node()));
                         }
 
                         let repliers_header = repliers_header_option.unwrap();
@@ -179,7 +231,9 @@ impl Show {
                         let divs_option = repliers_header.as_node().select("div").ok().map_or(None, |x| Some(x.collect::<Vec<_>>()));
 
                         if divs_option.is_none() {
-                            return Err("fail to build title and reply_count, reason: 'divs' not found");
+                            return Err(ParseError::new("fail to build title and reply_count, reason: 'divs' not found")
+                                                  .with_selector("div")
+                                                  .with_snippet(repliers_header.as_node()));
                         }
 
                         let divs = divs_option.unwrap();
@@ -189,7 +243,9 @@ impl Show {
                         let count = divs_enumerator.clone().count();
                         if  count < 2 {
                             error!("length of topic_data is invalid. length: {}", count);
-                            return Err(&"length of topic_data is invalid.");
+                            return Err(ParseError::new("length of topic_data is invalid.")
+                                                  .with_selector("div")
+                                                  .with_snippet(repliers_header.as_node()));
                         }
 
                         let title_option = match divs_enumerator.clone().filter(|&(i, _)| i == 0).map(|(i, e)| (i,e)).next() {
@@ -200,71 +256,78 @@ impl Show {
                             None => None
                         };
 
+                        // an unexpected count is left unknown, the page is still readable without it
                         let reply_count = match divs_enumerator.clone().filter(|&(i, _)| i == 1).map(|(i, e)| (i,e)).next() {
                             Some((i, div)) => {
                                 info!("{} => {:?}", i, div.text_contents());
                                 let re = Regex::new(r"^(?P<count>\d+)個回應$").expect("fail to build title and reply_count, reason: invalid regex");
                                 let s_trimmed = div.text_contents().trim().to_string();
-                                let cap_option = re.captures(&s_trimmed);
-                                if cap_option.is_none() {
-                                    None
-                                } else {
-                                    Some(cap_option.unwrap().name("count").unwrap_or("0").to_string())
-                                }
+                                re.captures(&s_trimmed)
+                                  .and_then(|cap| cap.name("count"))
+                                  .and_then(|count| count.parse::<usize>().ok())
                             },
                             None => None
                         };
 
-                        if  title_option.is_none() || reply_count.is_none() {
-                            return Err(&"fail to build title and reply_count, reason: 'topic_data' not found");
+                        if title_option.is_none() {
+                            return Err(ParseError::new("fail to build title and reply_count, reason: 'topic_data' not found")
+                                                  .with_snippet(repliers_header.as_node()));
                         }
 
                         Ok(
                             (
                                 title_option.unwrap().to_string(),
-                                reply_count.unwrap().to_string()
+                                reply_count
                             )
                         )
                     },
-                    None => Err(&"fail to build title and reply_count, reason: 'repliers_tr' not found")
+                    None => Err(ParseError::new("fail to build title and reply_count, reason: 'repliers_tr' not found")
+                                           .with_selector(".repliers tr"))
                 }
             },
-            Err(e) =>  Err("fail to build title and reply_count, reason: 'repliers_tr' not found")
+            Err(_) =>  Err(ParseError::new("fail to build title and reply_count, reason: 'repliers_tr' not found")
+                                      .with_selector(".repliers tr"))
         };
 
     }
 
-    fn parse_show_reply_items(&self, document: &NodeRef) -> Result<Vec<ShowReplyItem>, &'static str>  {
+    fn parse_show_reply_items(&mut self, document: &NodeRef) -> Result<Vec<ShowReplyItem>, ParseError>  {
 
         let replies_data_option = document.select(".repliers tr[userid][username]").ok().map_or(None, |x| Some(x.collect::<Vec<_>>()) );
 
         if replies_data_option.is_none() {
-            return Err(&"fail to parse show reply items, reason: 'replies_data' not found");
+            return Err(ParseError::new("fail to parse show reply items, reason: 'replies_data' not found")
+                                  .with_selector(".repliers tr[userid][username]"));
         }
 
         let replies_data = replies_data_option.unwrap();
 
-        let show_replies = replies_data.iter().enumerate().map(reply_items_handler).collect::<Vec<_>>();
-
-        let err_show_reply_option = show_replies.iter().filter(|x| x.is_err()).next();
+        let mut result = Vec::new();
 
-        if err_show_reply_option.is_some() {
-            return Err(&"fail to parse show reply items, reason: 'error show reply item' was found");
+        // one odd reply should not cost the whole page, it is shown as a placeholder instead
+        for (index, tr) in replies_data.iter().enumerate() {
+            match reply_items_handler((index, tr)) {
+                Ok(reply) => result.push(reply),
+                Err(e) => {
+                    result.push(placeholder_reply_item(tr, &e));
+                    self.diagnostics.push(e);
+                }
+            }
         }
 
-        let result = show_replies.iter().map(|x| x.clone().unwrap() ).collect::<Vec<_>>();
-
         Ok(result)
     }
 }
 
 
-fn reply_items_handler((index,tr): (usize, &::kuchiki::NodeDataRef<::kuchiki::ElementData>)) -> Result<ShowReplyItem, &'static str> {
+fn reply_items_handler((index,tr): (usize, &::kuchiki::NodeDataRef<::kuchiki::ElementData>)) -> Result<ShowReplyItem, ParseError> {
+    let reply_error = |message: &str| ParseError::new(message).with_index(index).with_snippet(tr.as_node());
+
     let tr_attrs = (&tr.attributes).borrow();
     let userid_option = tr_attrs.get("userid");
 
     if userid_option.is_none() {
-        return Err("fail to parse show reply item, reason: 'userid' not found");
+        return Err(reply_error("fail to parse show reply item, reason: 'userid' not found"));
     }
 
     let userid = userid_option.unwrap();
@@ -272,7 +335,7 @@ fn reply_items_handler((index,tr): (usize, &::kuchiki::NodeDataRef<::kuchiki::El
     let username_option = tr_attrs.get("username");
 
     if username_option.is_none() {
-        return Err("fail to parse show reply item, reason: 'userame' not found");
+        return Err(reply_error("fail to parse show reply item, reason: 'userame' not found"));
     }
 
     let username = username_option.unwrap();
@@ -280,7 +343,8 @@ fn reply_items_handler((index,tr): (usize, &::kuchiki::NodeDataRef<::kuchiki::El
     let content_elm_option = tr.as_node().select(".repliers_right .ContentGrid").ok().map_or(None, |mut x| x.next());
 
     if content_elm_option.is_none() {
-        return Err("fail to parse show reply item, reason: 'content_elm' not found");
+        return Err(reply_error("fail to parse show reply item, reason: 'content_elm' not found")
+                       .with_selector(".repliers_right .ContentGrid"));
     }
     let content_elm = content_elm_option.unwrap();
 
@@ -290,7 +354,7 @@ fn reply_items_handler((index,tr): (usize, &::kuchiki::NodeDataRef<::kuchiki::El
     let content_result = String::from_utf8(vec);
 
     if content_result.is_err() {
-        return Err("fail to parse show reply item, reason: 'content' invalid");
+        return Err(reply_error("fail to parse show reply item, reason: 'content' invalid"));
     }
 
     let content = content_result.unwrap();
@@ -300,14 +364,15 @@ fn reply_items_handler((index,tr): (usize, &::kuchiki::NodeDataRef<::kuchiki::El
                     .map_or(None, |mut x| Some(x.text_contents()));
 
     if datatime_option.is_none() {
-        return Err("fail to parse show reply item, reason: 'datatime' not found");
+        return Err(reply_error("fail to parse show reply item, reason: 'datatime' not found")
+                       .with_selector(".repliers_right span"));
     }
 
     let datatime = datatime_option.unwrap();
 
     let mut vec: Vec<NodeType> = Vec::new();
 
-    vec = recursive(content_elm.as_node());
+    vec = normalize_whitespace(recursive(content_elm.as_node()));
 
     Ok(
         ShowReplyItem {
@@ -315,11 +380,126 @@ fn reply_items_handler((index,tr): (usize, &::kuchiki::NodeDataRef<::kuchiki::El
             username: String::from(username),
             content: String::from(content),
             body: vec,
-            published_at: String::from(datatime),
+            published_at: Timestamp::from_hkt(&datatime, FORUM_TIME_FORMAT),
+            floor: reply_floor(tr.as_node()),
+            reply_id: reply_id(tr),
+            gender: reply_gender(tr.as_node()),
+            is_op: false,
+            is_moderator: is_moderator(tr.as_node()),
+            diagnostic: None,
         }
     )
 }
 
+fn placeholder_reply_item(tr: &::kuchiki::NodeDataRef<::kuchiki::ElementData>, e: &ParseError) -> ShowReplyItem {
+    let tr_attrs = (&tr.attributes).borrow();
+
+    let text = tr.as_node().text_contents()
+                           .lines()
+                           .map(|x| x.trim())
+                           .filter(|x| !x.is_empty())
+                           .collect::<Vec<_>>()
+                           .join("\n");
+
+    ShowReplyItem {
+        userid: tr_attrs.get("userid").unwrap_or("").to_string(),
+        username: tr_attrs.get("username").unwrap_or("").to_string(),
+        body: vec![NodeType::Text(TextNode { data: text })],
+        floor: reply_floor(tr.as_node()),
+        reply_id: reply_id(tr),
+        gender: reply_gender(tr.as_node()),
+        is_moderator: is_moderator(tr.as_node()),
+        diagnostic: Some(e.clone()),
+        ..Default::default()
+    }
+}
+
+// the "#12" printed next to the date, a "#3" written in the reply itself is not the floor
+fn reply_floor(tr: &NodeRef) -> Option<usize> {
+    let cells = match tr.select(".repliers_right") {
+        Ok(cells) => cells,
+        Err(_) => return None,
+    };
+    for cell in cells {
+        for text in cell.as_node().descendants().text_nodes() {
+            if text.as_node().ancestors().any(|x| has_class(&x, "ContentGrid")) {
+                continue;
+            }
+            let text = text.borrow().trim().to_string();
+            match FLOOR_RE.captures(&text).and_then(|cap| cap.name("floor")).and_then(|x| x.parse::<usize>().ok()) {
+                Some(floor) => return Some(floor),
+                None => {}
+            }
+        }
+    }
+    None
+}
+
+fn has_class(node: &NodeRef, class: &str) -> bool {
+    node.as_element().map_or(false, |x| {
+        (&x.attributes).borrow().get("class").map_or(false, |classes| classes.split_whitespace().any(|c| c == class))
+    })
+}
+
+// the row may carry it, otherwise the quote and reply links do
+fn reply_id(tr: &::kuchiki::NodeDataRef<::kuchiki::ElementData>) -> Option<String> {
+    match (&tr.attributes).borrow().get("replyid") {
+        Some(id) => return Some(id.trim().to_string()),
+        None => {}
+    }
+
+    let links = match tr.as_node().select("a[href]") {
+        Ok(links) => links,
+        Err(_) => return None,
+    };
+    for link in links {
+        let href = (&link.attributes).borrow().get("href").unwrap_or("").to_string();
+        match REPLY_ID_RE.captures(&href).and_then(|cap| cap.name("rid")) {
+            Some(rid) => return Some(rid.to_string()),
+            None => {}
+        }
+    }
+    None
+}
+
+// from the colour of the name in the left column, blueish for men and reddish for women
+fn reply_gender(tr: &NodeRef) -> Gender {
+    let names = match tr.select(".repliers_left a, .repliers_left font, .repliers_left span") {
+        Ok(names) => names,
+        Err(_) => return Gender::Unknown,
+    };
+    for name in names {
+        let color = span_style(&name).and_then(|x| x.color).and_then(|x| parse_css_color(&x));
+        match color {
+            Some((r, g, b)) if b > r && b > g => return Gender::Male,
+            Some((r, g, b)) if r > b && r > g => return Gender::Female,
+            _ => {}
+        }
+    }
+    Gender::Unknown
+}
+
+fn is_moderator(tr: &NodeRef) -> bool {
+    let left = match tr.select(".repliers_left").ok().and_then(|mut x| x.next()) {
+        Some(left) => left,
+        None => return false,
+    };
+
+    let text = left.as_node().text_contents();
+    if text.contains("版主") || text.contains("管理員") {
+        return true;
+    }
+
+    match left.as_node().select("img") {
+        Ok(imgs) => imgs.into_iter().any(|img| {
+            let attrs = (&img.attributes).borrow();
+            let src = attrs.get("src").unwrap_or("").to_lowercase();
+            src.contains("admin") || src.contains("moderator")
+        }),
+        Err(_) => false,
+    }
+}
+
 
 fn recursive(elm: &NodeRef) -> Vec<NodeType> {
 
@@ -337,12 +517,29 @@ fn recursive(elm: &NodeRef) -> Vec<NodeType> {
                 if element_data.name.local.trim().eq("blockquote") {
 
                     // println!("[{}] => [ELEMENT] => {:?}", index, child.children());
-                    let subvec = recursive(&child);
+                    let subvec = normalize_whitespace(recursive(&child));
                     let node = NodeType::BlockQuote(BlockQuoteNode { data: subvec });
                     vec.push(node);
                 } else if element_data.name.local.trim().eq("br") {
                     let node = NodeType::Br(BrNode {});
                     vec.push(node);
+                } else if element_data.name.local.trim().eq("pre") || is_preformatted(&element_data) {
+                    let text = preformatted_text(&child);
+                    let node = NodeType::Pre(PreNode { data: text.trim_matches('\n').to_string() });
+                    vec.push(node);
+                } else if element_data.name.local.trim().eq("hr") {
+                    let node = NodeType::Hr(HrNode {});
+                    vec.push(node);
+                } else if element_data.name.local.trim().eq("ul") || element_data.name.local.trim().eq("ol") {
+                    let items = child.children()
+                                     .filter(|x| is_element(x, "li"))
+                                     .map(|x| normalize_whitespace(recursive(&x)))
+                                     .collect::<Vec<_>>();
+                    let node = NodeType::List(ListNode { ordered: element_data.name.local.trim().eq("ol"), items: items });
+                    vec.push(node);
+                } else if element_data.name.local.trim().eq("table") {
+                    let node = NodeType::Table(TableNode { rows: table_rows(&child) });
+                    vec.push(node);
                 } else if element_data.name.local.trim().eq("img") {
 
                     let attrs = (&element_data.attributes).borrow();
@@ -351,6 +548,14 @@ fn recursive(elm: &NodeRef) -> Vec<NodeType> {
                     let node = NodeType::Image(ImageNode { data: url.to_string(), alt: alt.to_string() });
                     vec.push(node);
 
+                } else if let Some(url) = link_url(&element_data) {
+                    let subvec = recursive(&child);
+                    let node = NodeType::Link(LinkNode { url: url, data: subvec });
+                    vec.push(node);
+                } else if let Some(style) = span_style(&element_data) {
+                    let subvec = recursive(&child);
+                    let node = NodeType::Span(SpanNode { style: style, data: subvec });
+                    vec.push(node);
                 } else {
                     // println!("[{}] => [ELEMENT] => {:?}", index, child);
                     let mut subvec = recursive(&child);
@@ -362,18 +567,286 @@ fn recursive(elm: &NodeRef) -> Vec<NodeType> {
                 let d = rc.clone();
                 let b = d.borrow();
 
-                let s = b.trim().to_string();
+                // whitespace is collapsed once the whole block is known, see normalize_whitespace
+                let node = NodeType::Text(TextNode { data: b.to_string() });
+                vec.push(node);
+            }
+            _ => {}
+        }
+    }
+    vec
+}
 
-                if s == "\n" {
-                    // s = "\\n".to_string()
-                    continue;
+// a reply body given as a fragment of html, as the json apis do,
+// relative links and images are resolved against the page it came from
+pub fn parse_reply_body(html: &str, base_url: &str) -> Vec<NodeType> {
+    let document = ::kuchiki::parse_html().one(html);
+    resolve_urls(&document, base_url);
+    match document.select("body").ok().and_then(|mut x| x.next()) {
+        Some(body) => normalize_whitespace(recursive(body.as_node())),
+        None => normalize_whitespace(recursive(&document)),
+    }
+}
+
+fn resolve_urls(document: &NodeRef, base_url: &str) {
+    let base_url = match Url::parse(base_url) {
+        Ok(url) => url,
+        Err(_) => return,
+    };
+
+    for (selector, name) in vec![("a[href]", "href"), ("img[src]", "src")] {
+        let elements = match document.select(selector) {
+            Ok(elements) => elements.collect::<Vec<_>>(),
+            Err(_) => continue,
+        };
+        for element in elements {
+            let mut attrs = element.attributes.borrow_mut();
+            let resolved = match attrs.get(name) {
+                Some(value) if !value.starts_with('#') && !value.to_lowercase().starts_with("javascript:") => {
+                    base_url.join(value.trim()).ok().map(|x| x.into_string())
                 }
+                _ => None,
+            };
+            match (resolved, attrs.get_mut(name)) {
+                (Some(resolved), Some(value)) => *value = resolved,
+                _ => {}
+            }
+        }
+    }
+}
 
-                let node = NodeType::Text(TextNode { data: s });
-                vec.push(node);
+// collapses whitespace the way a browser lays out inline content, a run of spaces,
+// tabs and newlines becomes one space and there is none at the start or end of a line.
+// non-breaking spaces are kept, ascii art is drawn with them
+pub fn normalize_whitespace(vec: Vec<NodeType>) -> Vec<NodeType> {
+    let mut last_is_space = true;
+    let mut result = collapse_whitespace(vec, &mut last_is_space);
+    trim_line_end(&mut result);
+    result
+}
+
+fn collapse_whitespace(vec: Vec<NodeType>, last_is_space: &mut bool) -> Vec<NodeType> {
+    let mut result = Vec::new();
+    for node in vec {
+        match node {
+            NodeType::Text(n) => {
+                let text = collapse_text(&n.data, last_is_space);
+                if !text.is_empty() {
+                    result.push(NodeType::Text(TextNode { data: text }));
+                }
+            }
+            NodeType::Span(n) => {
+                let data = collapse_whitespace(n.data, last_is_space);
+                if !data.is_empty() {
+                    result.push(NodeType::Span(SpanNode { style: n.style, data: data }));
+                }
+            }
+            NodeType::Link(n) => {
+                let data = collapse_whitespace(n.data, last_is_space);
+                result.push(NodeType::Link(LinkNode { url: n.url, data: data }));
+            }
+            NodeType::Image(n) => {
+                *last_is_space = false;
+                result.push(NodeType::Image(n));
+            }
+            // everything else starts a new line
+            other => {
+                trim_line_end(&mut result);
+                *last_is_space = true;
+                result.push(other);
+            }
+        }
+    }
+    result
+}
+
+// removes the space collapse_whitespace may have left at the end of the line
+fn trim_line_end(vec: &mut Vec<NodeType>) {
+    loop {
+        let is_empty = match vec.last_mut() {
+            Some(&mut NodeType::Text(ref mut n)) => {
+                let len = n.data.trim_right_matches(' ').len();
+                n.data.truncate(len);
+                n.data.is_empty()
+            }
+            Some(&mut NodeType::Span(ref mut n)) => {
+                trim_line_end(&mut n.data);
+                n.data.is_empty()
+            }
+            Some(&mut NodeType::Link(ref mut n)) => {
+                trim_line_end(&mut n.data);
+                false
+            }
+            _ => false,
+        };
+
+        if !is_empty {
+            break;
+        }
+        vec.pop();
+    }
+}
+
+fn collapse_text(s: &str, last_is_space: &mut bool) -> String {
+    let mut text = String::new();
+    for c in s.chars() {
+        if c == ' ' || c == '\t' || c == '\n' || c == '\r' || c == '\x0c' {
+            if !*last_is_space {
+                text.push(' ');
+                *last_is_space = true;
             }
+        } else {
+            text.push(c);
+            *last_is_space = false;
+        }
+    }
+    text
+}
+
+// elements styled "white-space: pre" are kept verbatim like <pre>
+fn is_preformatted(element_data: &ElementData) -> bool {
+    let attrs = (&element_data.attributes).borrow();
+    attrs.get("style").map_or(false, |css| {
+        css.split(';').any(|declaration| {
+            let mut parts = declaration.splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(property), Some(value)) => {
+                    let value = value.trim().to_lowercase();
+                    property.trim().to_lowercase() == "white-space" && (value == "pre" || value == "pre-wrap")
+                }
+                _ => false,
+            }
+        })
+    })
+}
+
+// text of a preformatted block, <br> are line breaks
+fn preformatted_text(elm: &NodeRef) -> String {
+    let mut text = String::new();
+    for child in elm.children() {
+        if is_element(&child, "br") {
+            text.push('\n');
+        } else if let Some(rc) = child.as_text() {
+            text.push_str(&rc.borrow());
+        } else {
+            text.push_str(&preformatted_text(&child));
+        }
+    }
+    text
+}
+
+fn is_element(node: &NodeRef, name: &str) -> bool {
+    node.as_element().map_or(false, |x| x.name.local.trim().eq(name))
+}
+
+// rows of a table, also those inside <thead>, <tbody> and <tfoot> but not those of nested tables
+fn table_rows(table: &NodeRef) -> Vec<Vec<TableCellNode>> {
+    let mut rows = Vec::new();
+    for child in table.children() {
+        if is_element(&child, "tr") {
+            let cells = child.children()
+                             .filter(|x| is_element(x, "td") || is_element(x, "th"))
+                             .map(|x| TableCellNode { header: is_element(&x, "th"), data: normalize_whitespace(recursive(&x)) })
+                             .collect::<Vec<_>>();
+            rows.push(cells);
+        } else if is_element(&child, "thead") || is_element(&child, "tbody") || is_element(&child, "tfoot") {
+            rows.append(&mut table_rows(&child));
+        }
+    }
+    rows
+}
+
+// absolute url of an <a href>, None for anything that is not a link to follow
+fn link_url(element_data: &ElementData) -> Option<String> {
+    if !element_data.name.local.trim().eq("a") {
+        return None;
+    }
+
+    let attrs = (&element_data.attributes).borrow();
+    let href = attrs.get("href").unwrap_or("").trim();
+
+    if href.is_empty() || href.starts_with('#') || href.to_lowercase().starts_with("javascript:") {
+        return None;
+    }
+
+    Url::parse("http://forum1.hkgolden.com/view.aspx").ok()
+                                                     .and_then(|base_url| base_url.join(href).ok())
+                                                     .map(|url| url.into_string())
+}
+
+// inline formatting of an element, None when it does not change how the text looks
+fn span_style(element_data: &ElementData) -> Option<SpanStyle> {
+    let name = element_data.name.local.trim().to_lowercase();
+    let attrs = (&element_data.attributes).borrow();
+
+    let mut style: SpanStyle = Default::default();
+
+    match name.as_str() {
+        "b" | "strong" => style.bold = true,
+        "i" | "em" => style.italic = true,
+        "u" | "ins" => style.underline = true,
+        "s" | "strike" | "del" => style.strikethrough = true,
+        "font" => {
+            style.color = attrs.get("color").map(|x| x.trim().to_lowercase());
+            style.size = attrs.get("size").and_then(|x| x.trim().parse::<u8>().ok());
+        }
+        _ => {}
+    }
+
+    match attrs.get("style") {
+        Some(css) => apply_inline_css(&mut style, css),
+        None => {}
+    }
+
+    if style.is_plain() { None } else { Some(style) }
+}
+
+fn apply_inline_css(style: &mut SpanStyle, css: &str) {
+    for declaration in css.split(';') {
+        let mut parts = declaration.splitn(2, ':');
+        let (property, value) = match (parts.next(), parts.next()) {
+            (Some(property), Some(value)) => (property.trim().to_lowercase(), value.trim().to_lowercase()),
+            _ => continue,
+        };
+
+        match property.as_str() {
+            "color" => style.color = Some(value),
+            "font-weight" => style.bold = value == "bold" || value == "bolder" || value.parse::<u32>().map(|x| x >= 600).unwrap_or(false),
+            "font-style" => style.italic = value == "italic" || value == "oblique",
+            "text-decoration" | "text-decoration-line" => {
+                style.underline = style.underline || value.contains("underline");
+                style.strikethrough = style.strikethrough || value.contains("line-through");
+            }
+            "font-size" => style.size = css_font_size(&value),
             _ => {}
         }
     }
-    vec
+}
+
+// css font sizes on the 1 to 7 scale of <font size>
+fn css_font_size(value: &str) -> Option<u8> {
+    match value {
+        "xx-small" | "x-small" => Some(1),
+        "small" | "smaller" => Some(2),
+        "medium" => Some(3),
+        "large" | "larger" => Some(4),
+        "x-large" => Some(5),
+        "xx-large" => Some(6),
+        "xxx-large" => Some(7),
+        _ => {
+            let number = value.trim_right_matches(|c: char| c.is_alphabetic() || c == '%');
+            match number.parse::<f32>() {
+                Ok(px) if value.ends_with("px") => Some(match px as u32 {
+                    0..=10 => 1,
+                    11..=13 => 2,
+                    14..=16 => 3,
+                    17..=18 => 4,
+                    19..=24 => 5,
+                    25..=32 => 6,
+                    _ => 7,
+                }),
+                _ => None,
+            }
+        }
+    }
 }
@@ -1,11 +1,58 @@
-use std::path::Path;
+// metadata kept for every cache entry
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct CacheMeta {
+    pub url: String,
+    pub content_type: Option<String>,
+    pub fetched_at: i64,
+    // seconds the entry stays fresh, None when it never changes
+    pub ttl: Option<i64>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub size: u64,
+    pub accessed_at: i64,
+    // checksum of the body, set by the cache on write
+    pub checksum: Option<String>
+}
+
+impl CacheMeta {
+    pub fn is_fresh(&self, now: i64) -> bool {
+        match self.ttl {
+            Some(ttl) => now - self.fetched_at < ttl,
+            None => true,
+        }
+    }
+
+    pub fn last_used_at(&self) -> i64 {
+        if self.accessed_at > self.fetched_at { self.accessed_at } else { self.fetched_at }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct CacheEntry {
+    pub body: Vec<u8>,
+    pub meta: CacheMeta
+}
 
+// a keyed store, keys look like "html/topics/201701011200.html" or "img/{name}"
+// shared across threads, so implementations lock what they change themselves
 pub trait Cache {
-    fn read<P: AsRef<Path>, S: AsRef<Path>>(&self, cache_path: P,
-                                                  file_name: S)
-                                                  -> Result<Vec<u8>, String> where Self:Sized;
-    fn write<P: AsRef<Path>, S: AsRef<Path>>(&mut self, cache_path: P,
-                                                   file_name: S,
-                                                   s: Vec<u8>)
-                                                   -> Result<(), String> where Self:Sized;
+    fn read(&self, key: &str) -> Result<CacheEntry, String>;
+    // a read left out of the hit stats, for showing an entry again
+    fn read_uncounted(&self, key: &str) -> Result<CacheEntry, String> {
+        self.read(key)
+    }
+    fn read_meta(&self, key: &str) -> Result<CacheMeta, String>;
+    fn write(&self, key: &str, body: Vec<u8>, meta: CacheMeta) -> Result<(), String>;
+    fn write_meta(&self, key: &str, meta: CacheMeta) -> Result<(), String>;
+    // mark the entry as recently used
+    fn touch(&self, key: &str) -> Result<(), String>;
+    fn exists(&self, key: &str) -> bool;
+    fn delete(&self, key: &str) -> Result<(), String>;
+    // keys starting with the prefix, in sorted order
+    fn iter<'a>(&'a self, prefix: &str) -> Box<dyn Iterator<Item = String> + 'a>;
 }
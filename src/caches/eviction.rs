@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use caches::common::*;
+use caches::shared_cache::*;
+use caches::migration::migrate_image_layout;
+use config::CacheConfig;
+
+// keys of the entries on screen by screen, the evictor never removes them
+pub type PinnedKeys = Arc<Mutex<HashMap<&'static str, HashSet<String>>>>;
+
+// what a screen shows now takes the place of what it showed before
+pub fn pin_keys(pinned: &PinnedKeys, screen: &'static str, keys: Vec<String>) {
+    match pinned.lock() {
+        Ok(mut screens) => {
+            screens.insert(screen, keys.into_iter().collect());
+        }
+        Err(e) => error!("fail to pin keys. screen: {} reason: {}", screen, e),
+    }
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+pub struct EvictionReport {
+    pub scanned: usize,
+    pub evicted: usize,
+    pub evicted_bytes: u64,
+    pub remaining_bytes: u64
+}
+
+pub struct Evictor {}
+
+impl Evictor {
+    pub fn new(config: CacheConfig, cache: SharedCache, pinned: PinnedKeys, working: Arc<AtomicBool>) -> Self {
+
+        thread::spawn(move || {
+            let mut cache = cache;
+
+            let report = migrate_image_layout(&mut cache);
+            info!("[evictor] image layout migration {:?}", report);
+
+            while (*working).load(Ordering::Relaxed) {
+                let report = evict(&mut cache, config.max_bytes(), config.max_age_secs(), &pinned);
+                info!("[evictor] {:?}", report);
+                thread::sleep(config.eviction_interval());
+            }
+        });
+
+        Evictor {}
+    }
+}
+
+// drop entries unused for longer than max_age_secs, then the least recently used ones until the cache fits max_bytes
+pub fn evict(cache: &mut dyn Cache, max_bytes: u64, max_age_secs: i64, pinned: &PinnedKeys) -> EvictionReport {
+    let now = ::time::get_time().sec;
+    let mut report: EvictionReport = Default::default();
+
+    let mut entries = cache.iter("")
+                           .filter_map(|key| cache.read_meta(&key).ok().map(|meta| (key, meta)))
+                           .collect::<Vec<_>>();
+    entries.sort_by_key(|&(_, ref meta)| meta.last_used_at());
+
+    report.scanned = entries.len();
+    let mut total_bytes = entries.iter().fold(0, |acc, &(_, ref meta)| acc + meta.size);
+
+    for (key, meta) in entries {
+        let is_expired = now - meta.last_used_at() > max_age_secs;
+        if !is_expired && total_bytes <= max_bytes {
+            continue;
+        }
+
+        if is_pinned(pinned, &key) {
+            continue;
+        }
+
+        match cache.delete(&key) {
+            Ok(_) => {
+                report.evicted += 1;
+                report.evicted_bytes += meta.size;
+                total_bytes -= meta.size;
+            }
+            Err(e) => error!("[evictor] fail to delete. key: {} reason: {}", key, e),
+        }
+    }
+
+    report.remaining_bytes = total_bytes;
+    report
+}
+
+fn is_pinned(pinned: &PinnedKeys, key: &str) -> bool {
+    match pinned.lock() {
+        Ok(screens) => screens.values().any(|keys| keys.contains(key)),
+        // keep everything when in doubt
+        Err(_) => true,
+    }
+}
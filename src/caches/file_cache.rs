@@ -1,42 +1,241 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs::File;
 use std::fs;
 use std::io::{Read, Write};
+use std::time::UNIX_EPOCH;
+use std::process;
+
+use rustc_serialize::json;
 
 use caches::common::*;
+use caches::keys::key_kind;
+use caches::stats::StatsRecorder;
+use caches::file_lock::FileLock;
+use utility::checksum::checksum;
 
-pub struct FileCache {}
+// entries live under the root as "{key}", with the metadata next to them as "{key}.meta"
+// and an advisory lock file "{key}.lock" shared by readers and writers of the key
+pub struct FileCache {
+    root: PathBuf,
+    stats: Option<StatsRecorder>
+}
 
 impl FileCache {
     pub fn new() -> FileCache {
-        FileCache { }
+        FileCache::with_root("data/cache")
+    }
+
+    pub fn with_root<P: AsRef<Path>>(root: P) -> FileCache {
+        FileCache { root: root.as_ref().to_path_buf(), stats: None }
+    }
+
+    // count hits and misses of every read into the recorder
+    pub fn with_stats(mut self, stats: StatsRecorder) -> FileCache {
+        self.stats = Some(stats);
+        self
+    }
+
+    fn record(&self, key: &str, is_hit: bool) {
+        match self.stats {
+            Some(ref stats) => {
+                match stats.lock() {
+                    Ok(mut stats) => stats.record(key_kind(key), is_hit),
+                    Err(_) => {}
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+
+    fn meta_path(&self, key: &str) -> PathBuf {
+        self.root.join(format!("{}.meta", key))
+    }
+
+    fn lock_path(&self, key: &str) -> PathBuf {
+        self.root.join(format!("{}.lock", key))
+    }
+
+    fn read_entry(&self, key: &str) -> Result<CacheEntry, String> {
+        if !self.path(key).is_file() {
+            return Err(format!("{} not found", key));
+        }
+        let _lock = try!(FileLock::shared(&self.lock_path(key)));
+        let body = try!(read_file(&self.path(key)));
+        let meta = try!(self.read_meta_unlocked(key));
+
+        match meta.checksum {
+            Some(ref expected) if *expected != checksum(&body) => {
+                error!("file cache - checksum mismatch. key: {}", key);
+                return Err(format!("{} checksum mismatch", key));
+            }
+            _ => {}
+        }
+
+        Ok(CacheEntry { body: body, meta: meta })
+    }
+
+    fn read_meta_unlocked(&self, key: &str) -> Result<CacheMeta, String> {
+        let meta_option = read_file(&self.meta_path(key)).ok()
+                                                          .and_then(|buffer| String::from_utf8(buffer).ok())
+                                                          .and_then(|s| json::decode::<CacheMeta>(&s).ok());
+        match meta_option {
+            Some(meta) => Ok(meta),
+            None => {
+                // entries written before metadata existed are treated as stale
+                let metadata = try!(fs::metadata(self.path(key)).map_err(|e| e.to_string()));
+                let fetched_at = metadata.modified()
+                                         .ok()
+                                         .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                                         .map_or(0, |d| d.as_secs() as i64);
+                Ok(CacheMeta {
+                    fetched_at: fetched_at,
+                    ttl: Some(0),
+                    size: metadata.len(),
+                    accessed_at: fetched_at,
+                    ..Default::default()
+                })
+            }
+        }
+    }
+
+    fn write_meta_unlocked(&self, key: &str, meta: &CacheMeta) -> Result<(), String> {
+        let s = try!(json::encode(meta).map_err(|e| e.to_string()));
+        write_file_atomic(&self.meta_path(key), s.as_bytes())
     }
 }
 
 impl Cache for FileCache {
-    fn read<P: AsRef<Path>, S: AsRef<Path>>(&self,
-                                            cache_path: P,
-                                            file_name: S)
-                                            -> Result<Vec<u8>, String> {
-
-        let file_path = cache_path.as_ref().join(file_name);
-        let mut file = try!(File::open(file_path).map_err(|e| e.to_string()));
-        let mut buffer = Vec::new();
-        try!(file.read_to_end(&mut buffer).map_err(|e| e.to_string()));
-        Ok(buffer)
-
-    }
-    fn write<P: AsRef<Path>, S: AsRef<Path>>(&mut self,
-                                             cache_path: P,
-                                             file_name: S,
-                                             s: Vec<u8>)
-                                             -> Result<(), String> {
-
-        let file_path = cache_path.as_ref().join(file_name);
-        try!(fs::create_dir_all(&cache_path).map_err(|e| e.to_string()));
-        let mut file = try!(File::create(file_path).map_err(|e| e.to_string()));
-        try!(file.write_all(s.as_slice()).map_err(|e| e.to_string()));
+    fn read(&self, key: &str) -> Result<CacheEntry, String> {
+        let result = self.read_entry(key);
+        self.record(key, result.is_ok());
+        result
+    }
+
+    fn read_uncounted(&self, key: &str) -> Result<CacheEntry, String> {
+        self.read_entry(key)
+    }
+
+    fn read_meta(&self, key: &str) -> Result<CacheMeta, String> {
+        if !self.path(key).is_file() {
+            return Err(format!("{} not found", key));
+        }
+        let _lock = try!(FileLock::shared(&self.lock_path(key)));
+        self.read_meta_unlocked(key)
+    }
+
+    fn write(&self, key: &str, body: Vec<u8>, meta: CacheMeta) -> Result<(), String> {
+        try!(create_parent_dir(&self.path(key)));
+        let _lock = try!(FileLock::exclusive(&self.lock_path(key)));
+        let mut meta = meta;
+        meta.size = body.len() as u64;
+        meta.checksum = Some(checksum(&body));
+        try!(write_file_atomic(&self.path(key), body.as_slice()));
+        self.write_meta_unlocked(key, &meta)
+    }
+
+    fn write_meta(&self, key: &str, meta: CacheMeta) -> Result<(), String> {
+        try!(create_parent_dir(&self.path(key)));
+        let _lock = try!(FileLock::exclusive(&self.lock_path(key)));
+        self.write_meta_unlocked(key, &meta)
+    }
+
+    fn touch(&self, key: &str) -> Result<(), String> {
+        if !self.path(key).is_file() {
+            return Err(format!("{} not found", key));
+        }
+        let _lock = try!(FileLock::exclusive(&self.lock_path(key)));
+        let mut meta = try!(self.read_meta_unlocked(key));
+        meta.accessed_at = ::time::get_time().sec;
+        self.write_meta_unlocked(key, &meta)
+    }
+
+    fn exists(&self, key: &str) -> bool {
+        self.path(key).is_file()
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        if !self.path(key).is_file() {
+            return Err(format!("{} not found", key));
+        }
+        let _lock = try!(FileLock::exclusive(&self.lock_path(key)));
+        try!(fs::remove_file(self.path(key)).map_err(|e| e.to_string()));
+        // legacy entries have no metadata to remove
+        fs::remove_file(self.meta_path(key)).ok();
+        // the lock file stays, a writer waiting on it would otherwise hold a lock nobody else sees
         Ok(())
+    }
+
+    fn iter<'a>(&'a self, prefix: &str) -> Box<dyn Iterator<Item = String> + 'a> {
+        let dir = match prefix.rfind('/') {
+            Some(i) => &prefix[..i],
+            None => "",
+        };
+
+        let mut files = Vec::new();
+        walk(&self.root.join(dir), &mut files);
+
+        let mut keys = files.iter()
+                            .filter_map(|path| path.strip_prefix(&self.root).ok())
+                            .filter_map(|path| path.to_str())
+                            .map(|key| key.replace("\\", "/"))
+                            .filter(|key| key.starts_with(prefix) && !is_sidecar(key))
+                            .collect::<Vec<_>>();
+        keys.sort();
+        Box::new(keys.into_iter())
+    }
+}
+
+fn is_sidecar(key: &str) -> bool {
+    key.ends_with(".meta") || key.ends_with(".lock") || key.ends_with(".tmp")
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    let mut file = try!(File::open(path).map_err(|e| e.to_string()));
+    let mut buffer = Vec::new();
+    try!(file.read_to_end(&mut buffer).map_err(|e| e.to_string()));
+    Ok(buffer)
+}
+
+fn create_parent_dir(path: &Path) -> Result<(), String> {
+    match path.parent() {
+        Some(dir) => fs::create_dir_all(dir).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+// write into a temporary file first, so a crash never leaves a half written entry behind
+fn write_file_atomic(path: &Path, s: &[u8]) -> Result<(), String> {
+    let tmp_path = PathBuf::from(format!("{}.{}.tmp", path.display(), process::id()));
+    {
+        let mut file = try!(File::create(&tmp_path).map_err(|e| e.to_string()));
+        try!(file.write_all(s).map_err(|e| e.to_string()));
+        try!(file.sync_all().map_err(|e| e.to_string()));
+    }
+    match fs::rename(&tmp_path, path) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            fs::remove_file(&tmp_path).ok();
+            Err(e.to_string())
+        }
+    }
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
 
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            walk(&path, files);
+        } else {
+            files.push(path);
+        }
     }
 }
@@ -0,0 +1,59 @@
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+
+// an advisory lock on a "{key}.lock" file, released when dropped
+pub struct FileLock {
+    file: File
+}
+
+impl FileLock {
+    pub fn shared(path: &Path) -> Result<FileLock, String> {
+        FileLock::lock(path, false)
+    }
+
+    pub fn exclusive(path: &Path) -> Result<FileLock, String> {
+        FileLock::lock(path, true)
+    }
+
+    fn lock(path: &Path, exclusive: bool) -> Result<FileLock, String> {
+        let file = try!(OpenOptions::new().read(true)
+                                          .write(true)
+                                          .create(true)
+                                          .open(path)
+                                          .map_err(|e| e.to_string()));
+        try!(sys_lock(&file, exclusive));
+        Ok(FileLock { file: file })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        sys_unlock(&self.file);
+    }
+}
+
+#[cfg(unix)]
+fn sys_lock(file: &File, exclusive: bool) -> Result<(), String> {
+    use std::os::unix::io::AsRawFd;
+
+    let operation = if exclusive { ::libc::LOCK_EX } else { ::libc::LOCK_SH };
+    match unsafe { ::libc::flock(file.as_raw_fd(), operation) } {
+        0 => Ok(()),
+        _ => Err(::std::io::Error::last_os_error().to_string()),
+    }
+}
+
+#[cfg(unix)]
+fn sys_unlock(file: &File) {
+    use std::os::unix::io::AsRawFd;
+
+    unsafe { ::libc::flock(file.as_raw_fd(), ::libc::LOCK_UN) };
+}
+
+#[cfg(not(unix))]
+fn sys_lock(_file: &File, _exclusive: bool) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sys_unlock(_file: &File) {}
@@ -0,0 +1,55 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+
+use caches::common::*;
+use caches::keys::*;
+
+// "{key}\t{url}" per line, kept outside data/cache so it is never evicted as an entry
+pub const IMAGE_INDEX_PATH: &'static str = "data/cache.img-index.tsv";
+
+pub fn append(key: &str, url: &str) -> Result<(), String> {
+    let mut file = try!(OpenOptions::new().create(true)
+                                          .append(true)
+                                          .open(IMAGE_INDEX_PATH)
+                                          .map_err(|e| e.to_string()));
+    // one write per line, so lines from different workers do not interleave
+    file.write_all(format!("{}\t{}\n", key, url).as_bytes()).map_err(|e| e.to_string())
+}
+
+pub fn find(url: &str) -> Option<String> {
+    let file = match File::open(IMAGE_INDEX_PATH) {
+        Ok(file) => file,
+        Err(_) => return None,
+    };
+
+    BufReader::new(file).lines()
+                        .filter_map(|line| line.ok())
+                        .filter_map(|line| {
+                            let mut parts = line.splitn(2, '\t');
+                            match (parts.next(), parts.next()) {
+                                (Some(key), Some(line_url)) if line_url == url => Some(key.to_string()),
+                                _ => None,
+                            }
+                        })
+                        .last()
+}
+
+// write the index again from the urls kept in the entry metadata, dropping lines of evicted images
+pub fn rebuild(cache: &dyn Cache) -> Result<usize, String> {
+    let lines = cache.iter(IMAGE_PREFIX)
+                     .filter_map(|key| {
+                         match cache.read_meta(&key) {
+                             Ok(ref meta) if !meta.url.is_empty() => Some(format!("{}\t{}\n", key, meta.url)),
+                             _ => None,
+                         }
+                     })
+                     .collect::<Vec<_>>();
+
+    let tmp_path = format!("{}.tmp", IMAGE_INDEX_PATH);
+    {
+        let mut file = try!(File::create(&tmp_path).map_err(|e| e.to_string()));
+        try!(file.write_all(lines.concat().as_bytes()).map_err(|e| e.to_string()));
+    }
+    try!(fs::rename(&tmp_path, IMAGE_INDEX_PATH).map_err(|e| e.to_string()));
+    Ok(lines.len())
+}
@@ -0,0 +1,78 @@
+use rustc_serialize::base64::FromBase64;
+
+use utility::checksum::fnv1a64;
+
+pub const TOPICS_PREFIX: &'static str = "html/topics/";
+pub const IMAGE_PREFIX: &'static str = "img/";
+pub const IMAGE_FAILURE_PREFIX: &'static str = "img_failed/";
+pub const MODEL_PREFIX: &'static str = "model/";
+
+// pages of every backend live under "html/", told apart by their extension
+pub fn show_key(postid: &str, page: usize, extension: &str) -> String {
+    format!("html/{postid}/show_{page}.{extension}", postid = postid, page = page, extension = extension)
+}
+
+// snapshots are named by "%Y%m%d%H%M", so the newest one sorts last,
+// the suffix after the time tells the board and the backend
+pub fn topics_key(time: &str, suffix: &str) -> String {
+    format!("{prefix}{time}.{suffix}", prefix = TOPICS_PREFIX, time = time, suffix = suffix)
+}
+
+pub fn is_topics_key(key: &str, suffix: &str) -> bool {
+    key.starts_with(TOPICS_PREFIX) && key[TOPICS_PREFIX.len()..].splitn(2, '.').nth(1) == Some(suffix)
+}
+
+// image keys are "img/{ab}/{cd}/{hash}", a fixed length name no matter how long the url is
+pub fn image_file_name(url: &str) -> String {
+    format!("{:016x}", fnv1a64(url.as_bytes()))
+}
+
+fn sharded(prefix: &str, file_name: &str) -> String {
+    format!("{prefix}{a}/{b}/{file_name}",
+            prefix = prefix,
+            a = &file_name[0..2],
+            b = &file_name[2..4],
+            file_name = file_name)
+}
+
+pub fn image_key(url: &str) -> String {
+    sharded(IMAGE_PREFIX, &image_file_name(url))
+}
+
+pub fn image_failure_key(url: &str) -> String {
+    sharded(IMAGE_FAILURE_PREFIX, &image_file_name(url))
+}
+
+// keys of the old flat layout are "img/{url safe base64 of the url}"
+pub fn legacy_image_url(key: &str) -> Option<String> {
+    let file_name = if key.starts_with(IMAGE_PREFIX) {
+        &key[IMAGE_PREFIX.len()..]
+    } else if key.starts_with(IMAGE_FAILURE_PREFIX) {
+        &key[IMAGE_FAILURE_PREFIX.len()..]
+    } else {
+        return None;
+    };
+
+    if file_name.contains('/') {
+        return None;
+    }
+
+    file_name.from_base64().ok().and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+// the kind of entry a key belongs to, used for statistics and cleanup
+pub fn key_kind(key: &str) -> &'static str {
+    if key.starts_with(TOPICS_PREFIX) {
+        "topics"
+    } else if key.starts_with("html/") {
+        "html"
+    } else if key.starts_with(IMAGE_PREFIX) {
+        "image"
+    } else if key.starts_with(IMAGE_FAILURE_PREFIX) {
+        "image_failure"
+    } else if key.starts_with(MODEL_PREFIX) {
+        "model"
+    } else {
+        "other"
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use caches::common::*;
+use caches::keys::key_kind;
+use caches::stats::{HitCount, StatsRecorder};
+
+struct MemoryEntry {
+    entry: CacheEntry,
+    used_at: u64
+}
+
+#[derive(Default)]
+struct MemoryState {
+    entries: HashMap<String, MemoryEntry>,
+    tick: u64,
+    size: usize,
+    count: HitCount
+}
+
+// keeps the most recently used entries of another cache in memory, up to capacity bytes
+pub struct MemoryCache<C: Cache> {
+    inner: C,
+    state: Mutex<MemoryState>,
+    capacity: usize,
+    stats: Option<StatsRecorder>
+}
+
+impl<C: Cache> MemoryCache<C> {
+    pub fn new(inner: C, capacity: usize) -> Self {
+        MemoryCache {
+            inner: inner,
+            state: Mutex::new(Default::default()),
+            capacity: capacity,
+            stats: None
+        }
+    }
+
+    // count memory hits and misses as the "memory" kind, memory hits also under the kind of the key
+    // as the inner cache never sees them
+    pub fn with_stats(mut self, stats: StatsRecorder) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    pub fn hit_count(&self) -> HitCount {
+        self.state.lock().map(|state| state.count.clone()).unwrap_or_default()
+    }
+
+    fn record(&self, key: &str, is_hit: bool) {
+        match self.state.lock() {
+            Ok(mut state) => {
+                if is_hit { state.count.hits += 1 } else { state.count.misses += 1 }
+            }
+            Err(_) => {}
+        }
+        match self.stats {
+            Some(ref stats) => {
+                match stats.lock() {
+                    Ok(mut stats) => {
+                        stats.record("memory", is_hit);
+                        if is_hit {
+                            stats.record(key_kind(key), true);
+                        }
+                    }
+                    Err(_) => {}
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        let mut guard = match self.state.lock() {
+            Ok(guard) => guard,
+            Err(_) => return None,
+        };
+        let state = &mut *guard;
+        state.tick += 1;
+        let tick = state.tick;
+        match state.entries.get_mut(key) {
+            Some(memory_entry) => {
+                memory_entry.used_at = tick;
+                Some(memory_entry.entry.clone())
+            }
+            None => None,
+        }
+    }
+
+    fn put(&self, key: &str, entry: CacheEntry) {
+        // an entry bigger than the whole tier would only flush everything else
+        if entry.body.len() > self.capacity {
+            self.remove(key);
+            return;
+        }
+
+        let mut guard = match self.state.lock() {
+            Ok(guard) => guard,
+            Err(_) => return,
+        };
+        let state = &mut *guard;
+        state.tick += 1;
+        let tick = state.tick;
+        let size = entry.body.len();
+
+        let old_option = state.entries.insert(key.to_string(), MemoryEntry { entry: entry, used_at: tick });
+        match old_option {
+            Some(old) => state.size -= old.entry.body.len(),
+            None => {}
+        }
+        state.size += size;
+
+        while state.size > self.capacity {
+            let lru_key = match state.entries.iter().min_by_key(|&(_, x)| x.used_at) {
+                Some((k, _)) => k.clone(),
+                None => break,
+            };
+            match state.entries.remove(&lru_key) {
+                Some(old) => state.size -= old.entry.body.len(),
+                None => break,
+            }
+        }
+    }
+
+    fn update_meta(&self, key: &str, meta: &CacheMeta) {
+        match self.state.lock() {
+            Ok(mut state) => {
+                match state.entries.get_mut(key) {
+                    Some(memory_entry) => memory_entry.entry.meta = meta.clone(),
+                    None => {}
+                }
+            }
+            Err(_) => {}
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        match self.state.lock() {
+            Ok(mut state) => {
+                let old_option = state.entries.remove(key);
+                match old_option {
+                    Some(old) => state.size -= old.entry.body.len(),
+                    None => {}
+                }
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+impl<C: Cache> Cache for MemoryCache<C> {
+    fn read(&self, key: &str) -> Result<CacheEntry, String> {
+        match self.get(key) {
+            Some(entry) => {
+                self.record(key, true);
+                Ok(entry)
+            }
+            None => {
+                self.record(key, false);
+                let entry = try!(self.inner.read(key));
+                self.put(key, entry.clone());
+                Ok(entry)
+            }
+        }
+    }
+
+    fn read_uncounted(&self, key: &str) -> Result<CacheEntry, String> {
+        match self.get(key) {
+            Some(entry) => Ok(entry),
+            None => {
+                let entry = try!(self.inner.read_uncounted(key));
+                self.put(key, entry.clone());
+                Ok(entry)
+            }
+        }
+    }
+
+    fn read_meta(&self, key: &str) -> Result<CacheMeta, String> {
+        match self.get(key) {
+            Some(entry) => Ok(entry.meta),
+            None => self.inner.read_meta(key),
+        }
+    }
+
+    fn write(&self, key: &str, body: Vec<u8>, meta: CacheMeta) -> Result<(), String> {
+        try!(self.inner.write(key, body.clone(), meta.clone()));
+        let mut meta = meta;
+        meta.size = body.len() as u64;
+        self.put(key, CacheEntry { body: body, meta: meta });
+        Ok(())
+    }
+
+    fn write_meta(&self, key: &str, meta: CacheMeta) -> Result<(), String> {
+        try!(self.inner.write_meta(key, meta.clone()));
+        self.update_meta(key, &meta);
+        Ok(())
+    }
+
+    fn touch(&self, key: &str) -> Result<(), String> {
+        try!(self.inner.touch(key));
+        match self.inner.read_meta(key) {
+            Ok(meta) => self.update_meta(key, &meta),
+            Err(_) => {}
+        }
+        Ok(())
+    }
+
+    fn exists(&self, key: &str) -> bool {
+        self.get(key).is_some() || self.inner.exists(key)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        self.remove(key);
+        self.inner.delete(key)
+    }
+
+    fn iter<'a>(&'a self, prefix: &str) -> Box<dyn Iterator<Item = String> + 'a> {
+        self.inner.iter(prefix)
+    }
+}
@@ -0,0 +1,72 @@
+use caches::common::*;
+use caches::keys::*;
+use caches::image_index;
+
+#[derive(Debug)]
+#[derive(Default)]
+pub struct MigrationReport {
+    pub migrated: usize,
+    pub failed: usize
+}
+
+// move images of the flat base64 layout into the hashed, sharded layout
+pub fn migrate_image_layout(cache: &mut dyn Cache) -> MigrationReport {
+    let mut report: MigrationReport = Default::default();
+
+    let legacy_keys = cache.iter(IMAGE_PREFIX)
+                           .chain(cache.iter(IMAGE_FAILURE_PREFIX))
+                           .filter_map(|key| legacy_image_url(&key).map(|url| (key, url)))
+                           .collect::<Vec<_>>();
+
+    if legacy_keys.is_empty() {
+        return report;
+    }
+
+    info!("[migration] {} images in the legacy layout", legacy_keys.len());
+
+    for (legacy_key, url) in legacy_keys {
+        let new_key = if legacy_key.starts_with(IMAGE_FAILURE_PREFIX) {
+            image_failure_key(&url)
+        } else {
+            image_key(&url)
+        };
+
+        match cache.read(&legacy_key) {
+            Ok(entry) => {
+                let mut meta = entry.meta;
+                meta.url = url.clone();
+                match cache.write(&new_key, entry.body, meta) {
+                    Ok(_) => {
+                        report.migrated += 1;
+                        delete_legacy(cache, &legacy_key);
+                    }
+                    Err(e) => {
+                        // kept for the next run, the image would be lost otherwise
+                        error!("[migration] fail to migrate. key: {} reason: {}", legacy_key, e);
+                        report.failed += 1;
+                    }
+                }
+            }
+            Err(e) => {
+                // a legacy entry that can not be read is not worth keeping
+                error!("[migration] fail to read. key: {} reason: {}", legacy_key, e);
+                report.failed += 1;
+                delete_legacy(cache, &legacy_key);
+            }
+        }
+    }
+
+    match image_index::rebuild(cache) {
+        Ok(count) => info!("[migration] image index rebuilt with {} entries", count),
+        Err(e) => error!("[migration] fail to rebuild image index. reason: {}", e),
+    }
+
+    report
+}
+
+fn delete_legacy(cache: &mut dyn Cache, legacy_key: &str) {
+    match cache.delete(legacy_key) {
+        Ok(_) => {}
+        Err(e) => error!("[migration] fail to delete. key: {} reason: {}", legacy_key, e),
+    }
+}
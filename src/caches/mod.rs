@@ -1,2 +1,11 @@
 pub mod common;
 pub mod file_cache;
+pub mod keys;
+pub mod eviction;
+pub mod stats;
+pub mod memory_cache;
+pub mod shared_cache;
+pub mod file_lock;
+pub mod image_index;
+pub mod migration;
+pub mod model_cache;
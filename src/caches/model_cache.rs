@@ -0,0 +1,67 @@
+use rustc_serialize::{Decodable, Encodable};
+use rustc_serialize::json::{self, Json};
+
+use caches::common::*;
+use caches::keys::*;
+use builders::PARSER_VERSION;
+
+// parsed pages are keyed by the checksum of their html, so a changed page never hits an old model
+pub fn model_key(kind: &str, source: &[u8]) -> String {
+    format!("{prefix}{kind}/{checksum}.json",
+            prefix = MODEL_PREFIX,
+            kind = kind,
+            checksum = ::utility::checksum::checksum(source))
+}
+
+// None when there is no model or it was written by another parser version
+pub fn read_model<T: Decodable>(cache: &dyn Cache, key: &str) -> Option<T> {
+    let entry = match cache.read(key) {
+        Ok(entry) => entry,
+        Err(_) => return None,
+    };
+
+    let json = match String::from_utf8(entry.body).ok().and_then(|s| Json::from_str(&s).ok()) {
+        Some(json) => json,
+        None => {
+            error!("model cache - invalid model. key: {}", key);
+            return None;
+        }
+    };
+
+    let version = json.find("version").and_then(|x| x.as_u64());
+    if version != Some(PARSER_VERSION as u64) {
+        info!("model cache - parser version changed. key: {} version: {:?}", key, version);
+        return None;
+    }
+
+    match json.find("model") {
+        Some(model) => {
+            let mut decoder = json::Decoder::new(model.clone());
+            match T::decode(&mut decoder) {
+                Ok(model) => Some(model),
+                Err(e) => {
+                    error!("model cache - fail to decode model. key: {} reason: {}", key, e);
+                    None
+                }
+            }
+        }
+        None => None,
+    }
+}
+
+pub fn write_model<T: Encodable>(cache: &mut dyn Cache, key: &str, model: &T) -> Result<(), String> {
+    let model_json = try!(json::encode(model).map_err(|e| e.to_string()));
+    let body = format!("{{\"version\":{version},\"model\":{model}}}",
+                       version = PARSER_VERSION,
+                       model = model_json);
+
+    let meta = CacheMeta {
+        url: key.to_string(),
+        content_type: Some(String::from("application/json")),
+        fetched_at: ::time::get_time().sec,
+        ttl: None,
+        ..Default::default()
+    };
+
+    cache.write(key, body.into_bytes(), meta)
+}
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use caches::common::*;
+
+// a cloneable handle, so the network workers and the screen use one cache
+// the cache locks what it changes itself, so one worker's disk i/o does not hold up the others
+#[derive(Clone)]
+pub struct SharedCache {
+    inner: Arc<Box<dyn Cache + Send + Sync>>
+}
+
+impl SharedCache {
+    pub fn new(cache: Box<dyn Cache + Send + Sync>) -> Self {
+        SharedCache { inner: Arc::new(cache) }
+    }
+}
+
+impl Cache for SharedCache {
+    fn read(&self, key: &str) -> Result<CacheEntry, String> {
+        self.inner.read(key)
+    }
+
+    fn read_uncounted(&self, key: &str) -> Result<CacheEntry, String> {
+        self.inner.read_uncounted(key)
+    }
+
+    fn read_meta(&self, key: &str) -> Result<CacheMeta, String> {
+        self.inner.read_meta(key)
+    }
+
+    fn write(&self, key: &str, body: Vec<u8>, meta: CacheMeta) -> Result<(), String> {
+        self.inner.write(key, body, meta)
+    }
+
+    fn write_meta(&self, key: &str, meta: CacheMeta) -> Result<(), String> {
+        self.inner.write_meta(key, meta)
+    }
+
+    fn touch(&self, key: &str) -> Result<(), String> {
+        self.inner.touch(key)
+    }
+
+    fn exists(&self, key: &str) -> bool {
+        self.inner.exists(key)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        self.inner.delete(key)
+    }
+
+    fn iter<'a>(&'a self, prefix: &str) -> Box<dyn Iterator<Item = String> + 'a> {
+        self.inner.iter(prefix)
+    }
+}
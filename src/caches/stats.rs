@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+
+use rustc_serialize::json;
+
+// kept outside data/cache, so it is never listed as a cache entry
+pub const STATS_PATH: &'static str = "data/cache.stats.json";
+
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct HitCount {
+    pub hits: u64,
+    pub misses: u64
+}
+
+impl HitCount {
+    pub fn ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total > 0 { self.hits as f64 / total as f64 } else { 0.0 }
+    }
+}
+
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct CacheStats {
+    pub kinds: BTreeMap<String, HitCount>
+}
+
+impl CacheStats {
+    pub fn load() -> CacheStats {
+        let mut s = String::new();
+        match File::open(STATS_PATH).and_then(|mut file| file.read_to_string(&mut s)) {
+            Ok(_) => json::decode(&s).unwrap_or_default(),
+            Err(_) => Default::default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let s = try!(json::encode(self).map_err(|e| e.to_string()));
+        let mut file = try!(File::create(STATS_PATH).map_err(|e| e.to_string()));
+        file.write_all(s.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    pub fn record(&mut self, kind: &str, is_hit: bool) {
+        let count = self.kinds.entry(kind.to_string()).or_insert_with(Default::default);
+        if is_hit {
+            count.hits += 1;
+        } else {
+            count.misses += 1;
+        }
+    }
+}
+
+// shared by every cache instance of the running app
+pub type StatsRecorder = Arc<Mutex<CacheStats>>;
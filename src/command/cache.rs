@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use caches::common::*;
+use caches::file_cache::*;
+use caches::keys::*;
+use caches::stats::*;
+use caches::migration::migrate_image_layout;
+use utility::image::*;
+
+const USAGE: &'static str = "usage:
+    hkg cache stats
+    hkg cache prune --older-than <age>     age like 30d, 12h or 45m
+    hkg cache clear [--images] [--html]
+    hkg cache verify [--delete]
+    hkg cache migrate";
+
+// entry point of "hkg cache ...", returns the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let mut cache = FileCache::new();
+
+    match args.first().map(|x| x.as_str()) {
+        Some("stats") => stats(&cache),
+        Some("prune") => {
+            let age_option = args.iter()
+                                 .position(|x| x == "--older-than")
+                                 .and_then(|i| args.get(i + 1))
+                                 .map(|x| parse_age(x));
+            match age_option {
+                Some(Ok(age)) => prune(&mut cache, age),
+                Some(Err(e)) => {
+                    println!("{}\n{}", e, USAGE);
+                    2
+                }
+                None => {
+                    println!("{}", USAGE);
+                    2
+                }
+            }
+        }
+        Some("clear") => {
+            let images = args.iter().any(|x| x == "--images");
+            let html = args.iter().any(|x| x == "--html");
+            if !images && !html {
+                println!("{}", USAGE);
+                return 2;
+            }
+            clear(&mut cache, images, html)
+        }
+        Some("verify") => {
+            let delete = args.iter().any(|x| x == "--delete");
+            verify(&mut cache, delete)
+        }
+        Some("migrate") => {
+            let report = migrate_image_layout(&mut cache);
+            println!("migrated {} images, {} failed", report.migrated, report.failed);
+            if report.failed == 0 { 0 } else { 1 }
+        }
+        _ => {
+            println!("{}", USAGE);
+            2
+        }
+    }
+}
+
+fn stats(cache: &dyn Cache) -> i32 {
+    let hit_counts = CacheStats::load();
+    let mut kinds: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+
+    for key in cache.iter("") {
+        let size = cache.read_meta(&key).map(|meta| meta.size).unwrap_or(0);
+        let kind = kinds.entry(key_kind(&key).to_string()).or_insert((0, 0));
+        kind.0 += 1;
+        kind.1 += size;
+    }
+
+    // kinds that were read but have nothing stored at the moment
+    for kind in hit_counts.kinds.keys() {
+        kinds.entry(kind.clone()).or_insert((0, 0));
+    }
+
+    println!("{:<14}{:>10}{:>12}{:>10}{:>10}{:>10}", "kind", "entries", "bytes", "hits", "misses", "hit ratio");
+
+    let mut total = (0, 0);
+    for (kind, &(entries, bytes)) in &kinds {
+        let count = hit_counts.kinds.get(kind).cloned().unwrap_or_default();
+        println!("{:<14}{:>10}{:>12}{:>10}{:>10}{:>9.1}%",
+                 kind,
+                 entries,
+                 format_bytes(bytes),
+                 count.hits,
+                 count.misses,
+                 count.ratio() * 100.0);
+        total.0 += entries;
+        total.1 += bytes;
+    }
+
+    println!("{:<14}{:>10}{:>12}", "total", total.0, format_bytes(total.1));
+    0
+}
+
+fn prune(cache: &mut dyn Cache, age: i64) -> i32 {
+    let now = ::time::get_time().sec;
+    let keys = cache.iter("").collect::<Vec<_>>();
+
+    let mut count = 0;
+    let mut bytes = 0;
+    for key in keys {
+        let meta = match cache.read_meta(&key) {
+            Ok(meta) => meta,
+            Err(_) => continue,
+        };
+
+        if now - meta.last_used_at() > age {
+            match cache.delete(&key) {
+                Ok(_) => {
+                    count += 1;
+                    bytes += meta.size;
+                }
+                Err(e) => println!("fail to delete {}: {}", key, e),
+            }
+        }
+    }
+
+    println!("pruned {} entries, {}", count, format_bytes(bytes));
+    0
+}
+
+fn clear(cache: &mut dyn Cache, images: bool, html: bool) -> i32 {
+    let keys = cache.iter("")
+                    .filter(|key| {
+                        match key_kind(key) {
+                            "image" | "image_failure" => images,
+                            "html" | "topics" | "model" => html,
+                            _ => false,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+    let mut count = 0;
+    for key in keys {
+        match cache.delete(&key) {
+            Ok(_) => count += 1,
+            Err(e) => println!("fail to delete {}: {}", key, e),
+        }
+    }
+
+    println!("cleared {} entries", count);
+    0
+}
+
+// images that are not images, empty pages and checksum mismatches come from older versions or interrupted writes
+fn verify(cache: &mut dyn Cache, delete: bool) -> i32 {
+    let keys = cache.iter("").collect::<Vec<_>>();
+
+    let mut bad_keys = Vec::new();
+    for key in keys {
+        let reason = match cache.read(&key) {
+            Ok(entry) => {
+                match key_kind(&key) {
+                    "image" if !is_image(&entry.body) => Some(String::from("not an image")),
+                    "html" | "topics" if entry.body.is_empty() => Some(String::from("empty page")),
+                    _ => None,
+                }
+            }
+            // includes entries whose checksum does not match
+            Err(e) => Some(e),
+        };
+
+        match reason {
+            Some(reason) => {
+                println!("{}: {}", key, reason);
+                bad_keys.push(key);
+            }
+            None => {}
+        }
+    }
+
+    if delete {
+        for key in &bad_keys {
+            match cache.delete(key) {
+                Ok(_) => {}
+                Err(e) => println!("fail to delete {}: {}", key, e),
+            }
+        }
+    }
+
+    println!("{} bad entries{}", bad_keys.len(), if delete && !bad_keys.is_empty() { ", deleted" } else { "" });
+
+    if bad_keys.is_empty() { 0 } else { 1 }
+}
+
+fn parse_age(s: &str) -> Result<i64, String> {
+    let (number, unit) = match s.chars().last() {
+        Some(c) if c.is_alphabetic() => (&s[..s.len() - 1], c),
+        _ => (s, 'd'),
+    };
+
+    let n = try!(number.parse::<i64>().map_err(|_| format!("invalid age: {}", s)));
+
+    match unit {
+        'd' => Ok(n * 24 * 60 * 60),
+        'h' => Ok(n * 60 * 60),
+        'm' => Ok(n * 60),
+        's' => Ok(n),
+        _ => Err(format!("invalid age unit: {}", s)),
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
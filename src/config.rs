@@ -0,0 +1,154 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use rustc_serialize::json;
+
+use utility::time::DisplayZone;
+
+// every section and field is optional, so a partial config file only overrides what it mentions
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct Config {
+    pub cache: Option<CacheConfig>,
+    pub icons: Option<IconsConfig>,
+    pub time: Option<TimeConfig>,
+    pub backend: Option<BackendConfig>
+}
+
+impl Config {
+    pub fn load<P: AsRef<Path>>(path: P) -> Config {
+        let mut s = String::new();
+        match File::open(path.as_ref()).and_then(|mut file| file.read_to_string(&mut s)) {
+            Ok(_) => {}
+            Err(e) => {
+                info!("config - use default config. path: {} reason: {}", path.as_ref().display(), e);
+                return Default::default();
+            }
+        }
+
+        match json::decode(&s) {
+            Ok(config) => config,
+            Err(e) => {
+                error!("config - fail to decode config, use default config. path: {} reason: {}", path.as_ref().display(), e);
+                Default::default()
+            }
+        }
+    }
+
+    pub fn cache(&self) -> CacheConfig {
+        self.cache.clone().unwrap_or_default()
+    }
+
+    pub fn icons(&self) -> IconsConfig {
+        self.icons.clone().unwrap_or_default()
+    }
+
+    pub fn time(&self) -> TimeConfig {
+        self.time.clone().unwrap_or_default()
+    }
+
+    pub fn backend(&self) -> BackendConfig {
+        self.backend.clone().unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct CacheConfig {
+    pub max_size_mb: Option<u64>,
+    pub max_age_days: Option<u64>,
+    pub eviction_interval_minutes: Option<u64>,
+    pub memory_size_mb: Option<u64>
+}
+
+impl CacheConfig {
+    pub fn max_bytes(&self) -> u64 {
+        self.max_size_mb.unwrap_or(512) * 1024 * 1024
+    }
+
+    pub fn max_age_secs(&self) -> i64 {
+        (self.max_age_days.unwrap_or(30) * 24 * 60 * 60) as i64
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        (self.memory_size_mb.unwrap_or(64) * 1024 * 1024) as usize
+    }
+
+    pub fn eviction_interval(&self) -> Duration {
+        Duration::from_secs(self.eviction_interval_minutes.unwrap_or(30) * 60)
+    }
+}
+
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct IconsConfig {
+    pub packs: Option<Vec<String>>,
+    pub images: Option<bool>
+}
+
+impl IconsConfig {
+    pub fn packs(&self) -> Vec<String> {
+        self.packs.clone().unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct TimeConfig {
+    pub timezone: Option<String>
+}
+
+impl TimeConfig {
+    pub fn display_zone(&self) -> DisplayZone {
+        match self.timezone {
+            Some(ref timezone) => match DisplayZone::parse(timezone) {
+                Ok(zone) => zone,
+                Err(e) => {
+                    error!("config - use local timezone. reason: {}", e);
+                    DisplayZone::Local
+                }
+            },
+            None => DisplayZone::Local,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct BackendConfig {
+    // "html" scrapes the desktop site, "json" reads the api of the mobile apps
+    pub name: Option<String>,
+    pub api_url: Option<String>,
+    pub lihkg_url: Option<String>
+}
+
+impl BackendConfig {
+    pub fn name(&self) -> String {
+        self.name.clone().unwrap_or(String::from("html"))
+    }
+
+    pub fn api_url(&self) -> String {
+        self.api_url.clone().unwrap_or(String::from("http://android-1-1.hkgolden.com"))
+    }
+
+    pub fn lihkg_url(&self) -> String {
+        self.lihkg_url.clone().unwrap_or(String::from("https://lihkg.com"))
+    }
+}
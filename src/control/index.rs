@@ -2,6 +2,8 @@ use std::sync::mpsc::Sender;
 use termion::event::Key;
 use state_manager::*;
 use resources::*;
+use model::UrlQueryItem;
+use backends::ForumChannel;
 
 use std::default::Default;
 
@@ -13,6 +15,10 @@ impl Index {
 
     pub fn new () -> Self { Index {} }
     pub fn handle(&mut self, c: ::termion::event::Key,app: &mut ::App)-> Option<i32> {
+        if app.index.get_channel_picker().is_some() {
+            return self.handle_channel_picker(c, app);
+        }
+
         match c {
             Key::Char('q') => {
                 ::screen::common::reset_screen();
@@ -23,19 +29,70 @@ impl Index {
                 app.status_bar.append(&app.screen_manager, &format!("r"));
                 Some(1)
             }
+            Key::Char('o') => {
+                let offline = !app.state_manager.is_offline();
+                app.state_manager.set_offline(offline);
+                app.status_bar.append(&app.screen_manager, if offline { "[OFFLINE:ON]" } else { "[OFFLINE:OFF]" });
+                Some(1)
+            }
+            Key::Char('c') => {
+                let channels = app.backends.channels();
+                app.status_bar.append(&app.screen_manager, &format!("[CHANNEL:{}]", channels.len()));
+                app.index.open_channel_picker(channels);
+                Some(1)
+            }
+            Key::Char('h') => {
+                if !app.state_manager.is_web_request() {
+                    let status_message = if app.index.get_history().is_some() {
+                        app.index.set_history(None);
+                        list_page(&app.forum_channel, &mut app.state_manager, &app.tx_req)
+                    } else {
+                        history_page(None, &app.forum_channel, &mut app.state_manager, &app.tx_req)
+                    };
+                    app.status_bar.append(&app.screen_manager, &format!("[HISTORY:{}]", status_message));
+                } else {
+                    app.status_bar.append(&app.screen_manager, "[HISTORY][BUSY]");
+                }
+                Some(1)
+            }
+            Key::Backspace => {
+                if app.index.get_history().is_some() && !app.state_manager.is_web_request() {
+                    app.index.set_history(None);
+                    let status_message = list_page(&app.forum_channel, &mut app.state_manager, &app.tx_req);
+                    app.status_bar.append(&app.screen_manager, &format!("[LIVE:{}]", status_message));
+                }
+                Some(1)
+            }
+            Key::Left | Key::Right => {
+                let key = match app.index.get_history() {
+                    Some(history) => if c == Key::Left { history.older() } else { history.newer() },
+                    None => None,
+                };
+                match key {
+                    Some(key) => {
+                        if !app.state_manager.is_web_request() {
+                            app.status_bar.append(&app.screen_manager, if c == Key::Left { "←" } else { "→" });
+                            let status_message = history_page(Some(key), &app.forum_channel, &mut app.state_manager, &app.tx_req);
+                            app.status_bar.append(&app.screen_manager, &format!("[HISTORY:{}]", status_message));
+                        }
+                    }
+                    None => {}
+                }
+                Some(1)
+            }
             Key::Char('\n') => {
                 if !app.state_manager.is_web_request() {
                     app.status_bar.append(&app.screen_manager, "[ENTER]");
                     let i = app.index.get_selected_topic();
-                    if i > 0 {
+                    if i > 0 && i <= app.list_topic_items.len() {
                         info!("select topic: {}", i);
                         let topic_item = &app.list_topic_items[i - 1];
-                        let postid = &topic_item.title.url_query.message;
+                        let url_query = &topic_item.title.url_query;
                         let page = 1;
-                        let status_message = show_page(&postid, page, &mut app.state_manager, &app.tx_req);
+                        let status_message = show_page(&url_query, page, &mut app.state_manager, &app.tx_req);
 
                         app.status_bar.append(&app.screen_manager,
-                                              &get_show_page_status_message(postid, page, &status_message));
+                                              &get_show_page_status_message(&url_query.message, page, &status_message));
                     }
                 } else {
                     app.status_bar.append(&app.screen_manager, "[ENTER][BUSY]");
@@ -76,16 +133,53 @@ impl Index {
         }
     }
 
+    fn handle_channel_picker(&mut self, c: ::termion::event::Key, app: &mut ::App) -> Option<i32> {
+        match c {
+            Key::Char(n) if n >= '1' && n <= '9' => {
+                let i = n as usize - '1' as usize;
+                let channel_option = app.index.get_channel_picker().and_then(|channels| channels.get(i).cloned());
+                match channel_option {
+                    Some(channel) => {
+                        if app.state_manager.is_web_request() {
+                            app.status_bar.append(&app.screen_manager, "[CHANNEL][BUSY]");
+                            return Some(1);
+                        }
+                        app.index.close_channel_picker();
+                        app.index.set_history(None);
+                        app.index.set_title(&channel.name);
+                        app.index.select_topic(0);
+                        ::screen::common::clear_screen();
+                        let status_message = list_page(&channel, &mut app.state_manager, &app.tx_req);
+                        app.status_bar.append(&app.screen_manager, &format!("[{}/{}:{}]", channel.forum, channel.channel, status_message));
+                        app.forum_channel = channel;
+                    }
+                    None => {}
+                }
+                Some(1)
+            }
+            Key::Esc | Key::Char('c') | Key::Char('q') | Key::Backspace => {
+                app.index.close_channel_picker();
+                ::screen::common::clear_screen();
+                Some(1)
+            }
+            _ => Some(1),
+        }
+    }
 }
 
-fn show_page(postid: &String, page: usize, state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
+fn show_page(url_query: &UrlQueryItem, page: usize, state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
 
     let ci = ChannelItem {
         extra: Some( ChannelItemType::Show(ChannelShowItem {
-                                         postid: postid.clone(),
+                                         forum: url_query.forum.clone(),
+                                         channel: url_query.channel.clone(),
+                                         postid: url_query.message.clone(),
                                          page: page,
+                                         show_item: None,
+                                         parse_error: None,
                                      })),
         result: Default::default(),
+        unavailable: false,
     };
 
     let status_message = match tx_req.send(ci) {
@@ -99,6 +193,47 @@ fn show_page(postid: &String, page: usize, state_manager: &mut StateManager, tx_
     status_message
 }
 
+fn list_page(channel: &ForumChannel, state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
+
+    let ci = ChannelItem {
+        extra: Some(ChannelItemType::Index(ChannelIndexItem {
+                                         forum: channel.forum.clone(),
+                                         channel: channel.channel.clone(),
+                                         ..Default::default()
+                                     })),
+        result: Default::default(),
+        unavailable: false,
+    };
+
+    send_request(ci, state_manager, tx_req)
+}
+
+fn history_page(key: Option<String>, channel: &ForumChannel, state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
+
+    let ci = ChannelItem {
+        extra: Some(ChannelItemType::History(ChannelHistoryItem {
+                                         forum: channel.forum.clone(),
+                                         channel: channel.channel.clone(),
+                                         key: key,
+                                         ..Default::default()
+                                     })),
+        result: Default::default(),
+        unavailable: false,
+    };
+
+    send_request(ci, state_manager, tx_req)
+}
+
+fn send_request(ci: ChannelItem, state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
+    match tx_req.send(ci) {
+        Ok(()) => {
+            state_manager.set_web_request(true);
+            "SOK".to_string()
+        }
+        Err(e) => format!("{}:{}", "SFAIL", e).to_string(),
+    }
+}
+
 fn get_show_page_status_message(postid: &String, page: usize, status_message: &String) -> String {
     format!("[{}-{}:{}]", postid, page, status_message)
 }
@@ -1,8 +1,14 @@
 use std::sync::mpsc::Sender;
+use std::env;
+use std::process::{Command, Stdio};
 use termion::event::Key;
+use url::Url;
 use status::*;
 use state_manager::*;
 use resources::*;
+use resources::filter_resource::cancel_walk;
+use model::{ShowItem, UrlQueryItem};
+use backends::{HKGOLDEN, LIHKG};
 
 pub struct Show {
 
@@ -12,6 +18,21 @@ impl Show {
 
     pub fn new () -> Self { Show {} }
     pub fn handle(&mut self, c: ::termion::event::Key,app: &mut ::App)-> Option<i32> {
+        if app.show.get_link_picker().is_some() {
+            return self.handle_link_picker(c, app);
+        }
+
+        if app.show.is_diagnostics_view() {
+            match c {
+                Key::Esc | Key::Char('d') | Key::Char('q') | Key::Backspace => {
+                    app.show.set_diagnostics_view(false);
+                    ::screen::common::clear_screen();
+                }
+                _ => {}
+            }
+            return Some(1);
+        }
+
         match c {
             Key::Char('q') => {
                 ::screen::common::reset_screen(); // print!("{}{}{}", termion::clear::All, style::Reset, termion::cursor::Show);
@@ -22,27 +43,92 @@ impl Show {
                 app.status_bar.append(&app.screen_manager, &format!("r"));
                 Some(1)
             }
+            Key::Char('o') => {
+                let offline = !app.state_manager.is_offline();
+                app.state_manager.set_offline(offline);
+                app.status_bar.append(&app.screen_manager, if offline { "[OFFLINE:ON]" } else { "[OFFLINE:OFF]" });
+                Some(1)
+            }
+            Key::Char('l') => {
+                let i = app.show.current_reply();
+                let links = match app.show_item.replies.get(i) {
+                    Some(reply) => ::reply_model::links(&reply.body),
+                    None => Vec::new(),
+                };
+                if links.is_empty() {
+                    app.status_bar.append(&app.screen_manager, "[LINK:NONE]");
+                } else {
+                    app.status_bar.append(&app.screen_manager, &format!("[LINK:{}]", links.len()));
+                    app.show.open_link_picker(links);
+                }
+                Some(1)
+            }
+            Key::Char('d') => {
+                let count = app.show_item.replies.iter().filter(|x| x.diagnostic.is_some()).count() +
+                            app.show.get_page_error().map_or(0, |_| 1);
+                if count == 0 {
+                    app.status_bar.append(&app.screen_manager, "[DIAG:NONE]");
+                } else {
+                    app.status_bar.append(&app.screen_manager, &format!("[DIAG:{}]", count));
+                    app.show.set_diagnostics_view(true);
+                }
+                Some(1)
+            }
+            Key::Char('f') | Key::Char('u') => {
+                // a second press goes back to the pages of the thread, even while the walk goes on
+                if app.show.get_filter().is_some() {
+                    cancel_walk(&app.filter_walk);
+                    let url_query = &app.show_item.url_query;
+                    let status_message = show_page(&url_query, 1, &mut app.state_manager, &app.tx_req);
+                    app.status_bar.append(&app.screen_manager, &get_show_page_status_message(&url_query.message, 1, &status_message));
+                    return Some(1);
+                }
+
+                if app.state_manager.is_web_request() {
+                    app.status_bar.append(&app.screen_manager, "[FILTER][BUSY]");
+                    return Some(1);
+                }
+
+                // the author of the thread, or whoever wrote the reply at the top
+                let userid_option = if c == Key::Char('f') {
+                    app.show_item.author_userid.clone()
+                } else {
+                    app.show_item.replies.get(app.show.current_reply()).map(|x| x.userid.clone()).filter(|x| !x.is_empty())
+                };
+                match userid_option {
+                    Some(userid) => {
+                        let status_message = filter_page(&app.show_item, &userid, &mut app.state_manager, &app.tx_req);
+                        app.status_bar.append(&app.screen_manager, &format!("[FILTER-{}:{}]", userid, status_message));
+                    }
+                    None => app.status_bar.append(&app.screen_manager, "[FILTER:NONE]"),
+                }
+                Some(1)
+            }
+            Key::Left | Key::Right if app.show.get_filter().is_some() => {
+                app.status_bar.append(&app.screen_manager, "[FILTER]");
+                Some(1)
+            }
             Key::Left => {
                 app.status_bar.append(&app.screen_manager, &format!("←"));
                 if app.show_item.page > 1 {
-                    let postid = &app.show_item.url_query.message;
+                    let url_query = &app.show_item.url_query;
                     let page = &app.show_item.page - 1;
-                    let status_message = show_page(&postid, page, &mut app.state_manager, &app.tx_req);
+                    let status_message = show_page(&url_query, page, &mut app.state_manager, &app.tx_req);
 
                     app.status_bar.append(&app.screen_manager,
-                                          &get_show_page_status_message(postid, page, &status_message));
+                                          &get_show_page_status_message(&url_query.message, page, &status_message));
                 }
                 Some(1)
             }
             Key::Right => {
                 app.status_bar.append(&app.screen_manager, &format!("→"));
                 if app.show_item.max_page > app.show_item.page {
-                    let postid = &app.show_item.url_query.message;
+                    let url_query = &app.show_item.url_query;
                     let page = &app.show_item.page + 1;
-                    let status_message = show_page(&postid, page, &mut app.state_manager, &app.tx_req);
+                    let status_message = show_page(&url_query, page, &mut app.state_manager, &app.tx_req);
 
                     app.status_bar.append(&app.screen_manager,
-                                          &get_show_page_status_message(postid, page, &status_message));
+                                          &get_show_page_status_message(&url_query.message, page, &status_message));
                 }
                 Some(1)
             }
@@ -78,6 +164,7 @@ impl Show {
             }
             Key::Backspace => {
                 app.status_bar.append(&app.screen_manager, "B");
+                cancel_walk(&app.filter_walk);
                 app.state_manager.update_state(Status::List); // state = Status::List;
                 ::screen::common::clear_screen();
                 Some(1)
@@ -85,17 +172,164 @@ impl Show {
             _ => None,
         }
     }
+
+    fn handle_link_picker(&mut self, c: ::termion::event::Key, app: &mut ::App) -> Option<i32> {
+        match c {
+            Key::Char(n) if n >= '1' && n <= '9' => {
+                let i = n as usize - '1' as usize;
+                let link_option = app.show.get_link_picker().and_then(|links| links.get(i).cloned());
+                match link_option {
+                    Some(link) => {
+                        app.show.close_link_picker();
+                        ::screen::common::clear_screen();
+                        let status_message = open_link(&link.url, app);
+                        app.status_bar.append(&app.screen_manager, &status_message);
+                    }
+                    None => {}
+                }
+                Some(1)
+            }
+            Key::Esc | Key::Char('l') | Key::Char('q') | Key::Backspace => {
+                app.show.close_link_picker();
+                ::screen::common::clear_screen();
+                Some(1)
+            }
+            _ => Some(1),
+        }
+    }
+}
+
+// links to another hkgolden or lihkg thread open in the app, everything else in $BROWSER
+fn open_link(url: &str, app: &mut ::App) -> String {
+    match thread_link(url).or_else(|| lihkg_thread_link(url)) {
+        Some((url_query, page)) => {
+            if app.state_manager.is_web_request() {
+                return String::from("[LINK][BUSY]");
+            }
+            let status_message = show_page(&url_query, page, &mut app.state_manager, &app.tx_req);
+            get_show_page_status_message(&url_query.message, page, &status_message)
+        }
+        None => {
+            match open_in_browser(url) {
+                Ok(_) => String::from("[LINK:BROWSER]"),
+                Err(e) => {
+                    error!("fail to open link. url: {} reason: {}", url, e);
+                    format!("[LINK:E-{}]", e)
+                }
+            }
+        }
+    }
+}
+
+// "view.aspx?message={postid}&page={page}" on any hkgolden host
+fn thread_link(url_str: &str) -> Option<(UrlQueryItem, usize)> {
+    let url = match Url::parse(url_str) {
+        Ok(url) => url,
+        Err(_) => return None,
+    };
+
+    let is_hkgolden = url.host_str().map_or(false, |host| host == "hkgolden.com" || host.ends_with(".hkgolden.com"));
+    if !is_hkgolden || !url.path().to_lowercase().ends_with("/view.aspx") {
+        return None;
+    }
+
+    let mut postid = None;
+    let mut channel = String::new();
+    let mut page = 1;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "message" => postid = Some(value.into_owned()),
+            "type" => channel = value.into_owned(),
+            "page" => page = value.parse::<usize>().unwrap_or(1),
+            _ => {}
+        }
+    }
+
+    postid.map(|postid| (UrlQueryItem { forum: String::from(HKGOLDEN), channel: channel, message: postid }, page))
+}
+
+// "/thread/{postid}" or "/thread/{postid}/page/{page}" on lihkg.com
+fn lihkg_thread_link(url_str: &str) -> Option<(UrlQueryItem, usize)> {
+    let url = match Url::parse(url_str) {
+        Ok(url) => url,
+        Err(_) => return None,
+    };
+
+    let is_lihkg = url.host_str().map_or(false, |host| host == "lihkg.com" || host.ends_with(".lihkg.com"));
+    if !is_lihkg {
+        return None;
+    }
+
+    let segments = url.path().split('/').filter(|x| !x.is_empty()).collect::<Vec<_>>();
+    let (postid, page) = match segments.as_slice() {
+        ["thread", postid] => (postid.to_string(), 1),
+        ["thread", postid, "page", page, ..] => (postid.to_string(), page.parse::<usize>().unwrap_or(1)),
+        _ => return None,
+    };
+
+    Some((UrlQueryItem { forum: String::from(LIHKG), channel: String::new(), message: postid }, page))
 }
 
+// $BROWSER may list several browsers separated by ':', "%s" marks where the url goes
+fn open_in_browser(url: &str) -> Result<(), String> {
+    let browser = try!(env::var("BROWSER").map_err(|_| String::from("$BROWSER not set")));
+    let command_line = browser.split(':').next().unwrap_or("").trim().to_string();
+
+    let mut parts = command_line.split_whitespace();
+    let program = try!(parts.next().ok_or(String::from("$BROWSER is empty")));
+
+    let mut args = parts.map(|x| x.replace("%s", url)).collect::<Vec<_>>();
+    if !command_line.contains("%s") {
+        args.push(url.to_string());
+    }
+
+    Command::new(program).args(&args)
+                         .stdin(Stdio::null())
+                         .stdout(Stdio::null())
+                         .stderr(Stdio::null())
+                         .spawn()
+                         .map(|_| ())
+                         .map_err(|e| e.to_string())
+}
+
+
+fn filter_page(show_item: &ShowItem, userid: &str, state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
+
+    let ci = ChannelItem {
+        extra: Some(ChannelItemType::Filter(ChannelFilterItem {
+                                         forum: show_item.url_query.forum.clone(),
+                                         channel: show_item.url_query.channel.clone(),
+                                         postid: show_item.url_query.message.clone(),
+                                         userid: userid.to_string(),
+                                         max_page: show_item.max_page,
+                                         ..Default::default()
+                                     })),
+        result: String::from(""),
+        unavailable: false,
+    };
+
+    match tx_req.send(ci) {
+        Ok(()) => {
+            state_manager.set_web_request(true);
+            "SOK".to_string()
+        }
+        Err(e) => format!("{}:{}", "SFAIL", e).to_string(),
+    }
+}
 
-fn show_page(postid: &String, page: usize, state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
+fn show_page(url_query: &UrlQueryItem, page: usize, state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
 
     let ci = ChannelItem {
         extra: Some(ChannelItemType::Show(ChannelShowItem {
-                                         postid: postid.clone(),
+                                         forum: url_query.forum.clone(),
+                                         channel: url_query.channel.clone(),
+                                         postid: url_query.message.clone(),
                                          page: page,
+                                         show_item: None,
+                                         parse_error: None,
                                      })),
         result: String::from(""),
+        unavailable: false,
     };
 
 
@@ -0,0 +1,79 @@
+use model::ListTopicItem;
+
+use caches::keys::TOPICS_PREFIX;
+
+// a jump of at least this many replies between two snapshots is marked
+const JUMP_THRESHOLD: usize = 10;
+
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum TopicMark {
+    Unchanged,
+    Appeared,
+    Disappeared,
+    Jumped(usize),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct HistoryView {
+    pub key: String,
+    pub snapshots: Vec<String>,
+    pub marks: Vec<TopicMark>
+}
+
+impl HistoryView {
+    pub fn position(&self) -> usize {
+        self.snapshots.iter().position(|x| *x == self.key).unwrap_or(0)
+    }
+
+    pub fn older(&self) -> Option<String> {
+        let position = self.position();
+        if position > 0 { self.snapshots.get(position - 1).cloned() } else { None }
+    }
+
+    pub fn newer(&self) -> Option<String> {
+        self.snapshots.get(self.position() + 1).cloned()
+    }
+
+    // "html/topics/201703041230.html" => "2017/03/04 12:30 [3/120]"
+    pub fn label(&self) -> String {
+        let name = self.key.trim_left_matches(TOPICS_PREFIX);
+        let time = name.split('.').next().unwrap_or(name);
+        let time_label = if time.len() == 12 {
+            format!("{}/{}/{} {}:{}", &time[0..4], &time[4..6], &time[6..8], &time[8..10], &time[10..12])
+        } else {
+            time.to_string()
+        };
+        format!("{} [{}/{}]", time_label, self.position() + 1, self.snapshots.len())
+    }
+
+    pub fn mark(&self, index: usize) -> TopicMark {
+        self.marks.get(index).cloned().unwrap_or(TopicMark::Unchanged)
+    }
+}
+
+// marks every current topic against the previous snapshot, and returns the topics that are gone
+pub fn compare(previous: &Vec<ListTopicItem>, current: &Vec<ListTopicItem>) -> (Vec<TopicMark>, Vec<ListTopicItem>) {
+    let marks = current.iter().map(|item| {
+        let previous_item_option = previous.iter().find(|x| x.title.url_query.message == item.title.url_query.message);
+        match previous_item_option {
+            Some(previous_item) => {
+                // nothing to compare when either count is unknown
+                match (item.reply_count, previous_item.reply_count) {
+                    (Some(count), Some(previous_count)) if count >= previous_count + JUMP_THRESHOLD => TopicMark::Jumped(count - previous_count),
+                    _ => TopicMark::Unchanged,
+                }
+            }
+            None => TopicMark::Appeared,
+        }
+    }).collect::<Vec<_>>();
+
+    let disappeared = previous.iter()
+                              .filter(|x| !current.iter().any(|item| item.title.url_query.message == x.title.url_query.message))
+                              .cloned()
+                              .collect::<Vec<_>>();
+
+    (marks, disappeared)
+}
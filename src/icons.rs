@@ -0,0 +1,183 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use rustc_serialize::json;
+use url::Url;
+
+use model::IconItem;
+
+pub const MANIFEST_NAME: &'static str = "icon.manifest.json";
+
+// the default pack is built into the binary, so it works from any working directory
+const DEFAULT_MANIFEST: &'static str = include_str!("../data/icon.manifest.json");
+
+const DEFAULT_IMAGES: &'static [(&'static str, &'static [u8])] = &[
+    ("369.gif", include_bytes!("../data/icon/369.gif")),
+    ("@.gif", include_bytes!("../data/icon/@.gif")),
+    ("ThumbDown.gif", include_bytes!("../data/icon/ThumbDown.gif")),
+    ("ThumbUp.gif", include_bytes!("../data/icon/ThumbUp.gif")),
+    ("adore.gif", include_bytes!("../data/icon/adore.gif")),
+    ("agree.gif", include_bytes!("../data/icon/agree.gif")),
+    ("angel.gif", include_bytes!("../data/icon/angel.gif")),
+    ("angry.gif", include_bytes!("../data/icon/angry.gif")),
+    ("ass.gif", include_bytes!("../data/icon/ass.gif")),
+    ("banghead.gif", include_bytes!("../data/icon/banghead.gif")),
+    ("biggrin.gif", include_bytes!("../data/icon/biggrin.gif")),
+    ("bomb.gif", include_bytes!("../data/icon/bomb.gif")),
+    ("bouncer.gif", include_bytes!("../data/icon/bouncer.gif")),
+    ("bouncy.gif", include_bytes!("../data/icon/bouncy.gif")),
+    ("bye.gif", include_bytes!("../data/icon/bye.gif")),
+    ("censored.gif", include_bytes!("../data/icon/censored.gif")),
+    ("chicken.gif", include_bytes!("../data/icon/chicken.gif")),
+    ("clown.gif", include_bytes!("../data/icon/clown.gif")),
+    ("cry.gif", include_bytes!("../data/icon/cry.gif")),
+    ("dead.gif", include_bytes!("../data/icon/dead.gif")),
+    ("devil.gif", include_bytes!("../data/icon/devil.gif")),
+    ("donno.gif", include_bytes!("../data/icon/donno.gif")),
+    ("fire.gif", include_bytes!("../data/icon/fire.gif")),
+    ("flowerface.gif", include_bytes!("../data/icon/flowerface.gif")),
+    ("frown.gif", include_bytes!("../data/icon/frown.gif")),
+    ("fuck.gif", include_bytes!("../data/icon/fuck.gif")),
+    ("good.gif", include_bytes!("../data/icon/good.gif")),
+    ("hehe.gif", include_bytes!("../data/icon/hehe.gif")),
+    ("hoho.gif", include_bytes!("../data/icon/hoho.gif")),
+    ("kill.gif", include_bytes!("../data/icon/kill.gif")),
+    ("kill2.gif", include_bytes!("../data/icon/kill2.gif")),
+    ("kiss.gif", include_bytes!("../data/icon/kiss.gif")),
+    ("love.gif", include_bytes!("../data/icon/love.gif")),
+    ("no.gif", include_bytes!("../data/icon/no.gif")),
+    ("offtopic.gif", include_bytes!("../data/icon/offtopic.gif")),
+    ("oh.gif", include_bytes!("../data/icon/oh.gif")),
+    ("photo.gif", include_bytes!("../data/icon/photo.gif")),
+    ("shocking.gif", include_bytes!("../data/icon/shocking.gif")),
+    ("slick.gif", include_bytes!("../data/icon/slick.gif")),
+    ("smile.gif", include_bytes!("../data/icon/smile.gif")),
+    ("sosad.gif", include_bytes!("../data/icon/sosad.gif")),
+    ("surprise.gif", include_bytes!("../data/icon/surprise.gif")),
+    ("tongue.gif", include_bytes!("../data/icon/tongue.gif")),
+    ("wink.gif", include_bytes!("../data/icon/wink.gif")),
+    ("wonder.gif", include_bytes!("../data/icon/wonder.gif")),
+    ("wonder2.gif", include_bytes!("../data/icon/wonder2.gif")),
+    ("yipes.gif", include_bytes!("../data/icon/yipes.gif")),
+    ("z.gif", include_bytes!("../data/icon/z.gif")),
+];
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum IconImage {
+    Embedded(&'static [u8]),
+    File(PathBuf),
+    Missing,
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Icon {
+    pub src: String,
+    pub alt: String,
+    pub fallback: Option<String>,
+    pub image: IconImage
+}
+
+impl Icon {
+    pub fn image_bytes(&self) -> Result<Vec<u8>, String> {
+        match self.image {
+            IconImage::Embedded(bytes) => Ok(bytes.to_vec()),
+            IconImage::File(ref path) => {
+                let mut buffer = Vec::new();
+                try!(File::open(path).and_then(|mut f| f.read_to_end(&mut buffer))
+                                     .map_err(|e| format!("{}: {}", path.display(), e)));
+                Ok(buffer)
+            }
+            IconImage::Missing => Err(format!("no image for icon {}", self.src)),
+        }
+    }
+
+    // shown where images are not, the text or emoji of the manifest or else the alt text
+    pub fn fallback_text(&self) -> String {
+        self.fallback.clone().unwrap_or(self.alt.clone())
+    }
+}
+
+pub struct IconRegistry {
+    icons: Vec<Icon>
+}
+
+impl IconRegistry {
+    pub fn new() -> Self {
+        let items: Vec<IconItem> = json::decode(DEFAULT_MANIFEST).expect("fail to decode default icon manifest");
+
+        let icons = items.into_iter().map(|item| {
+            let image = match DEFAULT_IMAGES.iter().find(|&&(name, _)| name == item.src) {
+                Some(&(_, bytes)) => IconImage::Embedded(bytes),
+                None => IconImage::Missing,
+            };
+            Icon { src: item.src, alt: item.alt, fallback: item.fallback, image: image }
+        }).collect::<Vec<_>>();
+
+        IconRegistry {
+            icons: icons
+        }
+    }
+
+    // a pack is a directory with an icon.manifest.json and the images it names,
+    // its icons win over those loaded before
+    pub fn load_pack<P: AsRef<Path>>(&mut self, dir: P) -> Result<usize, String> {
+        let dir = dir.as_ref();
+        let manifest_path = dir.join(MANIFEST_NAME);
+
+        let mut s = String::new();
+        try!(File::open(&manifest_path).and_then(|mut f| f.read_to_string(&mut s))
+                                       .map_err(|e| format!("{}: {}", manifest_path.display(), e)));
+
+        let items: Vec<IconItem> = try!(json::decode(&s).map_err(|e| format!("{}: {}", manifest_path.display(), e)));
+
+        let mut icons = items.into_iter().map(|item| {
+            let path = dir.join(&item.src);
+            let image = if path.is_file() { IconImage::File(path) } else { IconImage::Missing };
+            Icon { src: item.src, alt: item.alt, fallback: item.fallback, image: image }
+        }).collect::<Vec<_>>();
+
+        let count = icons.len();
+        icons.extend(self.icons.drain(..));
+        self.icons = icons;
+
+        Ok(count)
+    }
+
+    // by the file name of the image first, an alt text can be shared or changed by the forum
+    pub fn find(&self, src: &str, alt: &str) -> Option<&Icon> {
+        let file_name = src.split(|c: char| c == '?' || c == '#')
+                           .next()
+                           .and_then(|x| x.rsplit('/').next())
+                           .unwrap_or("");
+
+        // a posted picture can share the file name of an icon, "imgur.com/x/smile.gif"
+        if !file_name.is_empty() && (!is_posted_image(alt) || is_icon_path(src)) {
+            match self.icons.iter().find(|x| x.src.eq_ignore_ascii_case(file_name)) {
+                Some(icon) => return Some(icon),
+                None => {}
+            }
+        }
+
+        let alt = alt.trim();
+        self.icons.iter().find(|x| x.alt == alt)
+    }
+}
+
+fn is_posted_image(alt: &str) -> bool {
+    let alt = alt.trim();
+    alt.starts_with("[img]") && alt.ends_with("[/img]")
+}
+
+// relative to the page, or under /faces/ on an hkgolden host
+fn is_icon_path(src: &str) -> bool {
+    match Url::parse(src) {
+        Ok(url) => {
+            let is_hkgolden = url.host_str().map_or(false, |host| host == "hkgolden.com" || host.ends_with(".hkgolden.com"));
+            is_hkgolden && url.path().contains("/faces/")
+        }
+        Err(_) => true,
+    }
+}
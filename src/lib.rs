@@ -9,31 +9,38 @@ extern crate time;
 extern crate url;
 extern crate regex;
 extern crate crossbeam;
+#[cfg(unix)]
+extern crate libc;
 
+#[macro_use]
+extern crate lazy_static;
 #[macro_use]
 extern crate log;
 extern crate log4rs;
 
+pub mod config;
 pub mod caches;
 pub mod resources;
 pub mod status;
 pub mod state_manager;
 pub mod screen_manager;
+pub mod history;
+pub mod icons;
 pub mod utility;
 pub mod reply_model;
 pub mod model;
 pub mod web;
 pub mod responser;
 pub mod builders;
+pub mod backends;
 pub mod screen;
 pub mod control;
+pub mod command;
 
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{Arc, Mutex};
 
 pub struct App <'a>{
-    pub index_builder: builders::index::Index,
-    pub show_builder: builders::show::Show,
     pub state_manager: state_manager::StateManager,
     pub screen_manager: screen_manager::ScreenManager,
     // pub icon_collection: &'a Box<Vec<model::IconItem>>,
@@ -45,6 +52,11 @@ pub struct App <'a>{
     pub show: screen::show::Show,
 
     pub image_request_count_lock: Arc<Mutex<usize>>,
+    pub pinned_keys: caches::eviction::PinnedKeys,
+    pub filter_walk: resources::filter_resource::FilterWalk,
+    pub backends: backends::Backends,
+    // the board the topic list shows
+    pub forum_channel: backends::ForumChannel,
     pub tx_req: &'a Sender<resources::ChannelItem>,
     pub rx_res: &'a Receiver<resources::ChannelItem>,
 
@@ -20,12 +20,20 @@ use rustc_serialize::json;
 use termion::input::TermRead;
 use termion::raw::IntoRawMode;
 use hkg::status::*;
-use hkg::model::IconItem;
+use hkg::icons::IconRegistry;
+use hkg::screen::style::inline_image_support;
 use hkg::state_manager::*;
 use hkg::screen_manager::*;
 use hkg::resources::*;
+use hkg::resources::filter_resource::FilterWalk;
 use hkg::web::*;
 use hkg::responser::*;
+use hkg::caches::eviction::*;
+use hkg::caches::stats::*;
+use hkg::caches::file_cache::*;
+use hkg::caches::memory_cache::*;
+use hkg::caches::shared_cache::*;
+use std::collections::HashMap;
 use std::thread;
 
 fn main() {
@@ -35,6 +43,16 @@ fn main() {
 
     info!("app start");
 
+    let args = std::env::args().collect::<Vec<_>>();
+
+    if args.get(1).map_or(false, |arg| arg == "cache") {
+        std::process::exit(hkg::command::cache::run(&args[2..]));
+    }
+
+    let is_offline = args.iter().skip(1).any(|arg| arg == "--offline");
+
+    let config = hkg::config::Config::load("config/hkg.json");
+
     // Clear the screen.
     hkg::screen::common::clear_screen();
 
@@ -49,21 +67,39 @@ fn main() {
     let working = Arc::new(AtomicBool::new(true));
     let control = Arc::downgrade(&working);
 
+    let offline = Arc::new(AtomicBool::new(is_offline));
+
+    let pinned_keys: PinnedKeys = Arc::new(Mutex::new(HashMap::new()));
+    let filter_walk: FilterWalk = Arc::new(Mutex::new(None));
+    let backends = hkg::backends::from_config(&config.backend());
+    let stats: StatsRecorder = Arc::new(Mutex::new(CacheStats::load()));
+
+    // pages and images recently used stay in memory in front of data/cache
+    let cache = {
+        let file_cache = FileCache::new().with_stats(stats.clone());
+        SharedCache::new(Box::new(MemoryCache::new(file_cache, config.cache().memory_bytes()).with_stats(stats.clone())))
+    };
+
     let mut app = {
 
         let stdout = {
             Box::new(_stdout.lock().into_raw_mode().expect("fail to lock stdout"))
         };
 
-        let icon_collection: Box<Vec<IconItem>> = {
-            let icon_manifest_string = hkg::utility::readfile(String::from("data/icon.manifest.json"));
-            Box::new(json::decode(&icon_manifest_string).expect("fail to lock stdout"))
+        let icons = {
+            let mut icons = IconRegistry::new();
+            for pack in config.icons().packs() {
+                match icons.load_pack(&pack) {
+                    Ok(count) => info!("icons - load {} icons from pack {}", count, pack),
+                    Err(e) => error!("icons - fail to load pack {}, reason: {}", pack, e),
+                }
+            }
+            icons
         };
+        let inline_images = config.icons().images.unwrap_or_else(inline_image_support);
 
         hkg::App {
-            index_builder: hkg::builders::index::Index::new(),
-            show_builder: hkg::builders::show::Show::new(),
-            state_manager: StateManager::new(tx_state),
+            state_manager: StateManager::new(tx_state, offline.clone()),
             screen_manager: ScreenManager::new(),
 
             // initialize empty page
@@ -72,9 +108,13 @@ fn main() {
 
             status_bar: hkg::screen::status_bar::StatusBar::new(),
             index: hkg::screen::index::Index::new(),
-            show: hkg::screen::show::Show::new(icon_collection),
+            show: hkg::screen::show::Show::new(icons, inline_images, config.time().display_zone(), cache.clone()),
 
             image_request_count_lock: Arc::new(Mutex::new(0)),
+            pinned_keys: pinned_keys.clone(),
+            filter_walk: filter_walk.clone(),
+            backends: backends.clone(),
+            forum_channel: backends.default_channel(),
             tx_req: &tx_req,
             rx_res: &rx_res,
 
@@ -82,15 +122,23 @@ fn main() {
         }
     };
 
-    Requester::new(rx_req, tx_res, working.clone());
+    Requester::new(rx_req, tx_res, working.clone(), offline.clone(), cache.clone(), backends.clone(), filter_walk.clone());
+
+    // cache eviction runs now and then periodically
+    Evictor::new(config.cache(), cache.clone(), pinned_keys.clone(), working.clone());
 
     let respsoner = Responser::new();
 
     let mut index_control = hkg::control::index::Index::new();
     let mut show_control = hkg::control::show::Show::new();
 
+    if app.state_manager.is_offline() {
+        app.status_bar.append(&app.screen_manager, "[OFFLINE]");
+    }
+
     // topics request
-    let status_message = list_page(&mut app.state_manager, &tx_req);
+    app.index.set_title(&app.forum_channel.name);
+    let status_message = list_page(&app.forum_channel, &mut app.state_manager, &tx_req);
     app.status_bar.append(&app.screen_manager, &status_message);
 
 
@@ -170,13 +218,28 @@ fn main() {
 
         thread::sleep(std::time::Duration::from_millis(50));
     }
+
+    match stats.lock() {
+        Ok(stats) => {
+            match stats.save() {
+                Ok(_) => {}
+                Err(e) => error!("fail to save cache stats. reason: {}", e),
+            }
+        }
+        Err(e) => error!("fail to lock cache stats. reason: {}", e),
+    }
 }
 
-fn list_page(state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
+fn list_page(channel: &hkg::backends::ForumChannel, state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
 
     let ci = ChannelItem {
-        extra: Some(ChannelItemType::Index(Default::default())),
-        result: Default::default()
+        extra: Some(ChannelItemType::Index(ChannelIndexItem {
+                                         forum: channel.forum.clone(),
+                                         channel: channel.channel.clone(),
+                                         ..Default::default()
+                                     })),
+        result: Default::default(),
+        unavailable: false
     };
 
     let status_message = match tx_req.send(ci) {
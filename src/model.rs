@@ -1,4 +1,6 @@
 use reply_model::*;
+use builders::common::ParseError;
+use utility::time::parse_hkt;
 
 #[derive(Debug)]
 #[derive(RustcDecodable)]
@@ -30,24 +32,36 @@ pub struct ListTopicAuthorItem {
 pub struct ListTopicItem {
     pub title: ListTopicTitleItem,
     pub author: ListTopicAuthorItem,
-    pub last_replied_date: String,
-    pub last_replied_time: String,
-    pub reply_count: String,
-    pub rating: String,
+    pub last_replied_at: Timestamp,
+    // None when the forum showed something other than a number
+    pub reply_count: Option<usize>,
+    pub rating: Option<i32>,
 }
 
 
 #[derive(Debug)]
 #[derive(RustcDecodable)]
 #[derive(RustcEncodable)]
+#[derive(Clone)]
 #[derive(Default)]
 pub struct ShowItem {
     pub url_query: UrlQueryItem,
     pub title: String,
-    pub reply_count: String,
+    pub reply_count: Option<usize>,
     pub page: usize,
     pub max_page: usize,
-    pub replies: Vec<ShowReplyItem>
+    pub replies: Vec<ShowReplyItem>,
+    // named by the thread, or the first reply on page 1; later pages take it from page 1 in the cache
+    pub author_userid: Option<String>
+}
+
+impl ShowItem {
+    pub fn set_author(&mut self, userid: Option<String>) {
+        for reply in &mut self.replies {
+            reply.is_op = userid.as_ref().map_or(false, |x| *x == reply.userid);
+        }
+        self.author_userid = userid;
+    }
 }
 
 #[derive(Debug)]
@@ -60,7 +74,33 @@ pub struct ShowReplyItem {
     pub username: String,
     pub content: String,
     pub body: Vec<NodeType>,
-    pub published_at: String
+    pub published_at: Timestamp,
+    pub floor: Option<usize>,
+    pub reply_id: Option<String>,
+    pub gender: Gender,
+    pub is_op: bool,
+    pub is_moderator: bool,
+    // Some when the reply could not be parsed, the body then only holds its raw text
+    pub diagnostic: Option<ParseError>
+}
+
+// hkgolden colours names blue for men and pink for women
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum Gender {
+    Unknown,
+    Male,
+    Female,
+}
+
+impl Default for Gender {
+    fn default() -> Self {
+        Gender::Unknown
+    }
 }
 
 #[derive(Debug)]
@@ -68,7 +108,9 @@ pub struct ShowReplyItem {
 #[derive(RustcEncodable)]
 #[derive(Clone)]
 #[derive(Default)]
+// where a thread lives, the forum is a backend name and empty for the default one
 pub struct UrlQueryItem {
+    pub forum: String,
     pub channel: String,
     pub message: String
 }
@@ -79,5 +121,27 @@ pub struct UrlQueryItem {
 #[derive(Default)]
 pub struct IconItem {
     pub src: String,
-    pub alt: String
+    pub alt: String,
+    pub fallback: Option<String>
+}
+
+// a time as printed by the forum, with the instant it stands for when it could be parsed
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct Timestamp {
+    pub text: String,
+    // seconds since the unix epoch
+    pub seconds: Option<i64>
+}
+
+impl Timestamp {
+    pub fn from_hkt(text: &str, format: &str) -> Self {
+        Timestamp {
+            text: text.trim().to_string(),
+            seconds: parse_hkt(text, format).map(|x| x.timestamp())
+        }
+    }
 }
@@ -7,6 +7,12 @@ pub enum NodeType {
     Image(ImageNode),
     BlockQuote(BlockQuoteNode),
     Br(BrNode),
+    Span(SpanNode),
+    Link(LinkNode),
+    List(ListNode),
+    Table(TableNode),
+    Hr(HrNode),
+    Pre(PreNode),
 }
 
 #[derive(Clone)]
@@ -26,6 +32,13 @@ pub struct ImageNode {
     pub alt: String
 }
 
+impl ImageNode {
+    // a picture posted with [img] tags, downloaded in the background, unlike icons
+    pub fn is_posted(&self) -> bool {
+        (self.data.starts_with("http") || self.data.starts_with("https")) && self.alt.starts_with("[img]") && self.alt.ends_with("[/img]")
+    }
+}
+
 #[derive(Clone)]
 #[derive(Debug)]
 #[derive(RustcDecodable)]
@@ -40,3 +53,149 @@ pub struct BrNode {
 pub struct BlockQuoteNode {
     pub data: Vec<NodeType>,
 }
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+pub struct SpanNode {
+    pub style: SpanStyle,
+    pub data: Vec<NodeType>,
+}
+
+// inline formatting of the markup, nested spans are merged when rendered
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+#[derive(Default)]
+#[derive(PartialEq)]
+pub struct SpanStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    // css colour as written in the markup, like "red" or "#ff0000"
+    pub color: Option<String>,
+    // font size from 1 to 7, 3 is normal
+    pub size: Option<u8>,
+}
+
+impl SpanStyle {
+    // the style of a span nested inside this one
+    pub fn merge(&self, inner: &SpanStyle) -> SpanStyle {
+        SpanStyle {
+            bold: self.bold || inner.bold,
+            italic: self.italic || inner.italic,
+            underline: self.underline || inner.underline,
+            strikethrough: self.strikethrough || inner.strikethrough,
+            color: inner.color.clone().or(self.color.clone()),
+            size: inner.size.or(self.size),
+        }
+    }
+
+    pub fn is_plain(&self) -> bool {
+        *self == Default::default()
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+pub struct LinkNode {
+    // absolute url of the href
+    pub url: String,
+    pub data: Vec<NodeType>,
+}
+
+impl LinkNode {
+    pub fn text(&self) -> String {
+        text_contents(&self.data)
+    }
+}
+
+pub fn text_contents(nodes: &Vec<NodeType>) -> String {
+    nodes.iter().map(|node| match *node {
+        NodeType::Text(ref n) => n.data.clone(),
+        NodeType::Span(ref n) => text_contents(&n.data),
+        NodeType::Link(ref n) => text_contents(&n.data),
+        _ => String::new(),
+    }).collect::<Vec<_>>().join("")
+}
+
+// links of a reply body in reading order, the order they are numbered on screen
+pub fn links(nodes: &Vec<NodeType>) -> Vec<LinkNode> {
+    let mut result = Vec::new();
+    for node in nodes {
+        match *node {
+            NodeType::Link(ref n) => result.push(n.clone()),
+            NodeType::Span(ref n) => result.append(&mut links(&n.data)),
+            NodeType::BlockQuote(ref n) => result.append(&mut links(&n.data)),
+            NodeType::List(ref n) => {
+                for item in &n.items {
+                    result.append(&mut links(item));
+                }
+            }
+            NodeType::Table(ref n) => result.append(&mut n.links()),
+            _ => {}
+        }
+    }
+    result
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+pub struct ListNode {
+    // <ol> when true, <ul> otherwise
+    pub ordered: bool,
+    pub items: Vec<Vec<NodeType>>,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+pub struct TableNode {
+    pub rows: Vec<Vec<TableCellNode>>,
+}
+
+impl TableNode {
+    pub fn links(&self) -> Vec<LinkNode> {
+        let mut result = Vec::new();
+        for row in &self.rows {
+            for cell in row {
+                result.append(&mut links(&cell.data));
+            }
+        }
+        result
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+pub struct TableCellNode {
+    // <th> when true, <td> otherwise
+    pub header: bool,
+    pub data: Vec<NodeType>,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+pub struct HrNode {
+}
+
+// preformatted text, shown verbatim
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(RustcDecodable)]
+#[derive(RustcEncodable)]
+pub struct PreNode {
+    pub data: String,
+}
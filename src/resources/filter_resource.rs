@@ -0,0 +1,207 @@
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::Sender;
+use cancellation::CancellationTokenSource;
+
+use resources::*;
+use resources::web_resource::*;
+use resources::common::*;
+use resources::show_resource::*;
+use model::ShowItem;
+use caches::common::*;
+use backends::Backend;
+
+// the walk in progress, leaving the filter view cancels it
+pub type FilterWalk = Arc<Mutex<Option<Arc<CancellationTokenSource>>>>;
+
+// a new walk takes over from the one before it
+pub fn start_walk(walk: &FilterWalk, ct: Arc<CancellationTokenSource>) {
+    match walk.lock() {
+        Ok(mut current) => {
+            match current.take() {
+                Some(previous) => previous.cancel(),
+                None => {}
+            }
+            *current = Some(ct);
+        }
+        Err(e) => error!("filter resource - fail to lock walk. reason: {}", e),
+    }
+}
+
+pub fn cancel_walk(walk: &FilterWalk) {
+    match walk.lock() {
+        Ok(mut current) => {
+            match current.take() {
+                Some(ct) => ct.cancel(),
+                None => {}
+            }
+        }
+        Err(e) => error!("filter resource - fail to lock walk. reason: {}", e),
+    }
+}
+
+pub fn is_walking(walk: &FilterWalk) -> bool {
+    walk.lock().ok().and_then(|current| current.as_ref().map(|ct| !ct.is_canceled())).unwrap_or(false)
+}
+
+// walks every page of a thread through the show resource, keeping the replies of one user
+pub struct FilterResource<'a> {
+    wr: &'a mut WebResource,
+    cache: &'a mut dyn Cache,
+    backend: &'a dyn Backend,
+    channel: String,
+    offline: bool,
+    // where the replies found so far go after every page
+    tx_partial: Option<Sender<ChannelItem>>,
+    // checked between pages, the walk stops once the reader has left
+    ct: Option<Arc<CancellationTokenSource>>
+}
+
+impl<'a> FilterResource<'a> {
+    pub fn new(wr: &'a mut WebResource, cache: &'a mut dyn Cache, backend: &'a dyn Backend, channel: &str, offline: bool) -> Self {
+        FilterResource {
+            wr: wr,
+            cache: cache,
+            backend: backend,
+            channel: channel.to_string(),
+            offline: offline,
+            tx_partial: None,
+            ct: None
+        }
+    }
+
+    // a long thread takes a while to walk, the reader sees the replies as they are found
+    pub fn with_partial_results(mut self, tx: Sender<ChannelItem>) -> Self {
+        self.tx_partial = Some(tx);
+        self
+    }
+
+    pub fn with_cancellation(mut self, ct: Arc<CancellationTokenSource>) -> Self {
+        self.ct = Some(ct);
+        self
+    }
+
+    fn is_canceled(&self) -> bool {
+        self.ct.as_ref().map_or(false, |ct| ct.is_canceled())
+    }
+
+    fn filter_item(&self, extra: &ChannelFilterItem, page: usize, max_page: usize, missing_pages: &Vec<usize>, filtered: &Option<ShowItem>) -> ChannelItem {
+        // the replies read as one page covering the whole thread
+        let show_item = filtered.clone().map(|mut x| {
+            x.page = 1;
+            x.max_page = max_page;
+            let author_userid = x.author_userid.take();
+            x.set_author(author_userid);
+            x
+        });
+
+        ChannelItem {
+            extra: Some(ChannelItemType::Filter(ChannelFilterItem {
+                forum: self.backend.forum().to_string(),
+                channel: self.channel.clone(),
+                postid: extra.postid.clone(),
+                userid: extra.userid.clone(),
+                page: page,
+                max_page: max_page,
+                done: page >= max_page,
+                missing_pages: missing_pages.clone(),
+                show_item: show_item
+            })),
+            result: Default::default(),
+            unavailable: false,
+        }
+    }
+
+    fn fetch_page(&mut self, postid: &str, page: usize) -> ChannelItem {
+        let request = ChannelItem {
+            extra: Some(ChannelItemType::Show(ChannelShowItem {
+                forum: self.backend.forum().to_string(),
+                channel: self.channel.clone(),
+                postid: postid.to_string(),
+                page: page,
+                show_item: None,
+                parse_error: None
+            })),
+            result: Default::default(),
+            unavailable: false,
+        };
+        let mut show_resource = ShowResource::new(self.wr, self.cache, self.backend, &self.channel, self.offline);
+        show_resource.fetch(&request)
+    }
+}
+
+impl<'a> Resource for FilterResource<'a> {
+    fn fetch(&mut self, item: &ChannelItem) -> ChannelItem {
+        info!("filter resource #fetch");
+        match item.extra.clone() {
+            Some(ChannelItemType::Filter(extra)) => {
+                let mut filtered: Option<ShowItem> = None;
+                let mut missing_pages = Vec::new();
+                let mut error = String::new();
+
+                // the pages known to the reader, in case page 1 is not available
+                let mut page = 1;
+                let mut max_page = ::std::cmp::max(extra.max_page, 1);
+                while page <= max_page {
+                    if self.is_canceled() {
+                        info!("filter resource - walk canceled. postid: {} page: {}", extra.postid, page);
+                        let mut result = self.filter_item(&extra, max_page, max_page, &missing_pages, &filtered);
+                        result.result = String::from("canceled");
+                        return result;
+                    }
+
+                    let response = self.fetch_page(&extra.postid, page);
+                    let show_item = match response.extra {
+                        Some(ChannelItemType::Show(show)) => show.show_item,
+                        _ => None,
+                    };
+
+                    match show_item {
+                        Some(show_item) => {
+                            max_page = ::std::cmp::max(max_page, show_item.max_page);
+                            let replies = show_item.replies.into_iter().filter(|x| x.userid == extra.userid);
+                            match filtered {
+                                Some(ref mut filtered) => filtered.replies.extend(replies),
+                                None => {
+                                    // the first page read carries the title and the author of the thread
+                                    filtered = Some(ShowItem {
+                                        replies: replies.collect(),
+                                        ..show_item
+                                    });
+                                }
+                            }
+                        }
+                        None => {
+                            info!("filter resource - page skipped. postid: {} page: {} reason: {}", extra.postid, page, response.result);
+                            if error.is_empty() {
+                                error = response.result;
+                            }
+                            missing_pages.push(page);
+                        }
+                    }
+
+                    if page < max_page && filtered.is_some() {
+                        match self.tx_partial {
+                            Some(ref tx) => {
+                                let partial = self.filter_item(&extra, page, max_page, &missing_pages, &filtered);
+                                match tx.send(partial) {
+                                    Ok(_) => {}
+                                    Err(e) => error!("filter resource - fail to send partial result. reason: {}", e),
+                                }
+                            }
+                            None => {}
+                        }
+                    }
+                    page += 1;
+                }
+
+                let mut result = self.filter_item(&extra, max_page, max_page, &missing_pages, &filtered);
+                if filtered.is_none() {
+                    result.result = error;
+                    result.unavailable = self.offline;
+                }
+                result
+            }
+            _ => Default::default()
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use resources::*;
+use resources::common::*;
+use resources::page_parser::*;
+use model::ListTopicItem;
+use caches::common::*;
+use caches::keys::*;
+use backends::{Backend, snapshot_suffix};
+
+pub struct HistoryResource<'a> {
+    cache: &'a mut dyn Cache,
+    backend: &'a dyn Backend,
+    channel: String
+}
+
+impl<'a> HistoryResource<'a> {
+    pub fn new(cache: &'a mut dyn Cache, backend: &'a dyn Backend, channel: &str) -> Self {
+        HistoryResource {
+            cache: cache,
+            backend: backend,
+            channel: channel.to_string()
+        }
+    }
+
+    fn read_items(&mut self, key: &str) -> Result<Vec<ListTopicItem>, String> {
+        let entry = try!(self.cache.read(key));
+        parse_topics_page(self.cache, self.backend, &entry.body, &entry.meta.url).map_err(|e| e.to_string())
+    }
+}
+
+impl<'a> Resource for HistoryResource<'a> {
+    // answers with the requested snapshot and the one before it, to compare them
+    fn fetch(&mut self, item: &ChannelItem) -> ChannelItem {
+        info!("history resource #fetch");
+        match item.extra.clone() {
+            Some(ChannelItemType::History(extra)) => {
+                let suffix = snapshot_suffix(self.backend, &self.channel);
+                let snapshots = self.cache.iter(TOPICS_PREFIX).filter(|x| is_topics_key(x, &suffix)).collect::<Vec<_>>();
+
+                let position_option = match extra.key {
+                    Some(ref key) => snapshots.iter().position(|x| x == key),
+                    None => if snapshots.is_empty() { None } else { Some(snapshots.len() - 1) },
+                };
+
+                match position_option {
+                    Some(position) => {
+                        let key = snapshots[position].clone();
+                        let (items, error) = match self.read_items(&key) {
+                            Ok(items) => (items, Default::default()),
+                            Err(e) => {
+                                error!("history resource - fail to read snapshot. key: {} reason: {}", key, e);
+                                (Default::default(), e)
+                            }
+                        };
+
+                        // a broken previous snapshot only costs the comparison
+                        let previous = if position > 0 {
+                            self.read_items(&snapshots[position - 1]).ok()
+                        } else {
+                            None
+                        };
+
+                        ChannelItem {
+                            extra: Some(ChannelItemType::History(ChannelHistoryItem {
+                                forum: self.backend.forum().to_string(),
+                                channel: self.channel.clone(),
+                                key: Some(key),
+                                snapshots: snapshots,
+                                items: items,
+                                previous: previous
+                            })),
+                            result: error,
+                            unavailable: false,
+                        }
+                    }
+                    None => {
+                        ChannelItem {
+                            extra: Some(ChannelItemType::History(ChannelHistoryItem {
+                                forum: self.backend.forum().to_string(),
+                                channel: self.channel.clone(),
+                                key: None,
+                                snapshots: snapshots,
+                                ..Default::default()
+                            })),
+                            result: Default::default(),
+                            unavailable: true,
+                        }
+                    }
+                }
+            }
+            _ => Default::default()
+        }
+    }
+}
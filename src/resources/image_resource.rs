@@ -1,15 +1,20 @@
 use resources::*;
 use resources::common::*;
 use caches::common::*;
-
-use rustc_serialize::base64::{self, ToBase64};
+use caches::keys::*;
+use caches::image_index;
 
 use std::io::Read;
 
+use utility::image::*;
+
 use ::hyper::Client;
 use ::hyper::net::HttpsConnector;
 use ::hyper_native_tls::NativeTlsClient;
-use ::hyper::header::{Headers, UserAgent};
+use ::hyper::header::{Headers, UserAgent, ContentType, ContentLength};
+use ::hyper::client::Response;
+use ::hyper::status::StatusCode;
+use ::hyper::mime::{Mime, TopLevel};
 
 use std::thread;
 use std::sync::mpsc::channel;
@@ -28,13 +33,17 @@ use std::thread::park_timeout;
 use std::time::{Instant, Duration};
 use std::sync::RwLock;
 
-pub struct ImageResource<'a, T: 'a + Cache + Send> {
-    cache: &'a mut Box<T>,
-    client: Client
+const IMAGE_FAILURE_TTL: i64 = 6 * 60 * 60;
+const IMAGE_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+pub struct ImageResource<'a> {
+    cache: &'a mut dyn Cache,
+    client: Client,
+    offline: bool
 }
 
-impl<'a, T: 'a + Cache + Send> ImageResource<'a, T> {
-    pub fn new(cache: &'a mut Box<T>) -> Self {
+impl<'a> ImageResource<'a> {
+    pub fn new(cache: &'a mut dyn Cache, offline: bool) -> Self {
         let ssl = NativeTlsClient::new().unwrap();
         let connector = HttpsConnector::new(ssl);
         let mut client = Client::with_connector(connector);
@@ -43,12 +52,78 @@ impl<'a, T: 'a + Cache + Send> ImageResource<'a, T> {
 
         ImageResource {
             cache: cache,
-            client: client
+            client: client,
+            offline: offline
+        }
+    }
+
+    // negative cache entries keep the failure reason, so a broken image is not requested on every page load
+    fn find_failure(&self, url: &str) -> Option<String> {
+        match self.cache.read(&image_failure_key(url)) {
+            Ok(entry) => {
+                if entry.meta.is_fresh(::time::get_time().sec) {
+                    Some(String::from_utf8_lossy(&entry.body).into_owned())
+                } else {
+                    None
+                }
+            }
+            Err(_) => None,
+        }
+    }
+
+    fn write_failure(&mut self, url: &str, reason: &str) {
+        let key = image_failure_key(url);
+        let meta = CacheMeta {
+            url: url.to_string(),
+            fetched_at: ::time::get_time().sec,
+            ttl: Some(IMAGE_FAILURE_TTL),
+            ..Default::default()
+        };
+        match self.cache.write(&key, reason.to_string().into_bytes(), meta) {
+            Ok(_) => {}
+            Err(e) => error!("image resource - fail to write negative cache. reason: {}", e),
         }
     }
 }
 
-impl<'a, T: 'a + Cache + Send> Resource for ImageResource<'a, T> {
+fn read_image_response(mut resp: Response) -> Result<(Vec<u8>, String), String> {
+    if resp.status != StatusCode::Ok {
+        return Err(format!("http status {}", resp.status));
+    }
+
+    match resp.headers.get::<ContentType>() {
+        Some(&ContentType(Mime(TopLevel::Image, _, _))) => {}
+        Some(content_type) => return Err(format!("content type {}", content_type)),
+        // let the magic bytes decide
+        None => {}
+    }
+
+    let content_length = resp.headers.get::<ContentLength>().map(|&ContentLength(n)| n);
+
+    match content_length {
+        Some(n) if n > IMAGE_MAX_SIZE => return Err(format!("too large: {} bytes", n)),
+        _ => {}
+    }
+
+    let mut buffer = Vec::new();
+    try!(resp.by_ref().take(IMAGE_MAX_SIZE + 1).read_to_end(&mut buffer).map_err(|e| e.to_string()));
+
+    if buffer.len() as u64 > IMAGE_MAX_SIZE {
+        return Err(format!("too large: over {} bytes", IMAGE_MAX_SIZE));
+    }
+
+    match content_length {
+        Some(n) if n != buffer.len() as u64 => return Err(format!("truncated: {} of {} bytes", buffer.len(), n)),
+        _ => {}
+    }
+
+    match image_format(&buffer) {
+        Some(format) => Ok((buffer, format.content_type().to_string())),
+        None => Err(String::from("not an image")),
+    }
+}
+
+impl<'a> Resource for ImageResource<'a> {
     fn fetch(&mut self, item: &ChannelItem) -> ChannelItem {
         info!("[image_resource] #fetch {:?}", item.extra.clone());
         match item.extra.clone() {
@@ -57,18 +132,53 @@ impl<'a, T: 'a + Cache + Send> Resource for ImageResource<'a, T> {
                     ChannelItemType::Image(extra) => {
                         let url = extra.url;
                         let url2 = url.clone();
-                        let img_path = "data/cache/img/";
-                        let img_file_name = url.into_bytes().as_slice().to_base64(base64::URL_SAFE);
+                        let img_key = image_key(&url);
 
                         info!("image resource - before find in cache. url: {}", url2.clone());
-                        let read_result: Option<(bool, Vec<u8>, String)> = match self.cache.read(&img_path, &img_file_name) {
-                            Ok(result) => {
+                        let cached = match self.cache.read(&img_key) {
+                            Ok(entry) => {
+                                if !entry.meta.url.is_empty() && entry.meta.url != url2 {
+                                    info!("image resource - hash collision with {}, fetch again. url: {}", entry.meta.url, url2.clone());
+                                    None
+                                } else if is_image(&entry.body) {
+                                    Some(entry.body)
+                                } else {
+                                    info!("image resource - invalid image in cache, fetch again. url: {}", url2.clone());
+                                    None
+                                }
+                            }
+                            Err(_) => None
+                        };
+
+                        let read_result: Option<(bool, Vec<u8>, String)> = match cached {
+                            Some(result) => {
                                 info!("image resource - find in cache success. url:  {}", url2.clone());
+                                self.cache.touch(&img_key).ok();
                                 Some( (true, result, Default::default()) )
                             }
-                            Err(_) => {
+                            None => {
                                 info!("image resource - find in cache fail. url:  {}", url2.clone());
 
+                                if self.offline {
+                                    return ChannelItem {
+                                        extra: Some(ChannelItemType::Image(ChannelImageItem { url: url2, bytes: Vec::new(), from_cache: false })),
+                                        result: String::from("offline"),
+                                        unavailable: true,
+                                    };
+                                }
+
+                                match self.find_failure(&url2) {
+                                    Some(reason) => {
+                                        info!("image resource - skip failed image. url: {} reason: {}", url2.clone(), reason);
+                                        return ChannelItem {
+                                            extra: Some(ChannelItemType::Image(ChannelImageItem { url: url2, bytes: Vec::new(), from_cache: true })),
+                                            result: reason,
+                                            unavailable: false,
+                                        };
+                                    }
+                                    None => {}
+                                }
+
                                 let (tx_req, rx_req) = channel::<Option<(bool, Vec<u8>, String)>>();
                                 let tx_req2 = tx_req.clone();
 
@@ -79,20 +189,47 @@ impl<'a, T: 'a + Cache + Send> Resource for ImageResource<'a, T> {
                                 headers.set(UserAgent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_12_3) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/56.0.2924.87 Safari/537.36".to_owned()));
 
                                 match self.client.get(&url3).headers(headers).send() {
-                                    Ok(mut resp) => {
+                                    Ok(resp) => {
                                             // is_done.write().unwrap().store(true, Ordering::Relaxed);
                                             // (*working).store(false, Ordering::Relaxed);
                                             info!("image resource - http request success url:  {}", url3.clone());
-                                            let mut buffer = Vec::new();
-                                            resp.read_to_end(&mut buffer).expect("fail to read buffer from the http response");
-                                            self.cache.write(&img_path, &img_file_name, buffer.clone()).expect("fail to write cache");
-                                            (None)
+                                            match read_image_response(resp) {
+                                                Ok((buffer, content_type)) => {
+                                                    let meta = CacheMeta {
+                                                        url: url3.clone(),
+                                                        content_type: Some(content_type),
+                                                        fetched_at: ::time::get_time().sec,
+                                                        ttl: None,
+                                                        ..Default::default()
+                                                    };
+                                                    match self.cache.write(&img_key, buffer, meta) {
+                                                        Ok(_) => {
+                                                            match image_index::append(&img_key, &url3) {
+                                                                Ok(_) => {}
+                                                                Err(e) => error!("image resource - fail to update image index. reason: {}", e),
+                                                            }
+                                                            (None)
+                                                        }
+                                                        Err(e) => {
+                                                            error!("image resource - fail to write cache. url: {} reason: {}", url3.clone(), e);
+                                                            Some( (false, Vec::new(), e) )
+                                                        }
+                                                    }
+                                                }
+                                                Err(reason) => {
+                                                    info!("image resource - invalid image url: {} reason: {}", url3.clone(), reason);
+                                                    self.write_failure(&url3, &reason);
+                                                    Some( (false, Vec::new(), reason) )
+                                                }
+                                            }
                                         }
                                     Err(e) => {
                                         // is_done.write().unwrap().store(true, Ordering::Relaxed);
                                         // (*working).store(false, Ordering::Relaxed);
                                         info!("image resource - http request fail url:  {}", url3.clone());
-                                        Some( (false, Vec::new(), e.to_string()) )
+                                        let reason = e.to_string();
+                                        self.write_failure(&url3, &reason);
+                                        Some( (false, Vec::new(), reason) )
                                     }
                                 }
                             }
@@ -105,6 +242,7 @@ impl<'a, T: 'a + Cache + Send> Resource for ImageResource<'a, T> {
                                 let result_item = ChannelItem {
                                     extra: Some(ChannelItemType::Image(ChannelImageItem { url: url2, bytes: result, from_cache: from_cache })),
                                     result: reason,
+                                    unavailable: false,
                                 };
                                 result_item
                             },
@@ -112,6 +250,7 @@ impl<'a, T: 'a + Cache + Send> Resource for ImageResource<'a, T> {
                                 ChannelItem {
                                     extra: Some(ChannelItemType::Image(Default::default())),
                                     result: Default::default(),
+                                    unavailable: false,
                                 }
                             }
                         }
@@ -120,6 +259,7 @@ impl<'a, T: 'a + Cache + Send> Resource for ImageResource<'a, T> {
                         ChannelItem {
                             extra: Some(ChannelItemType::Image(Default::default())),
                             result: Default::default(),
+                            unavailable: false,
                         }
                     }
                 }
@@ -128,6 +268,7 @@ impl<'a, T: 'a + Cache + Send> Resource for ImageResource<'a, T> {
                 ChannelItem {
                     extra: Some(ChannelItemType::Image(Default::default())),
                     result: Default::default(),
+                    unavailable: false,
                 }
             }
         }
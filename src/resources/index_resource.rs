@@ -1,26 +1,56 @@
 use resources::*;
+use model::ListTopicItem;
 use resources::web_resource::*;
 use resources::common::*;
+use resources::page_parser::*;
 use caches::common::*;
+use caches::keys::*;
+use backends::{Backend, snapshot_suffix};
 
-pub struct IndexResource<'a, T: 'a + Cache> {
+// the topic list changes all the time, so a snapshot only stays fresh for a minute
+const TOPICS_TTL: i64 = 60;
+
+pub struct IndexResource<'a> {
     wr: &'a mut WebResource,
-    cache: &'a mut Box<T>,
-    url: &'static str
+    cache: &'a mut dyn Cache,
+    backend: &'a dyn Backend,
+    channel: String,
+    url: String,
+    offline: bool
 }
 
-impl<'a, T: 'a + Cache> IndexResource<'a, T> {
-    pub fn new(wr: &'a mut WebResource, cache: &'a mut Box<T>) -> Self {
+impl<'a> IndexResource<'a> {
+    pub fn new(wr: &'a mut WebResource, cache: &'a mut dyn Cache, backend: &'a dyn Backend, channel: &str, offline: bool) -> Self {
         IndexResource {
             wr: wr,
             cache: cache,
-            url: "http://archive.hkgolden.com/topics.aspx?type=BW"
+            backend: backend,
+            channel: channel.to_string(),
+            url: backend.topics_url(channel, 1),
+            offline: offline
         }
     }
-}
 
-impl<'a, T: 'a + Cache> Resource for IndexResource<'a, T> {
-    fn fetch(&mut self, item: &ChannelItem) -> ChannelItem {
+    fn latest_snapshot(&self) -> Option<(String, CacheEntry)> {
+        let suffix = snapshot_suffix(self.backend, &self.channel);
+        // snapshots are named by "%Y%m%d%H%M", so the newest one sorts last
+        let key_option = self.cache.iter(TOPICS_PREFIX).filter(|x| is_topics_key(x, &suffix)).last();
+        match key_option {
+            Some(key) => {
+                match self.cache.read(&key) {
+                    Ok(entry) => Some((key, entry)),
+                    Err(e) => {
+                        error!("index resource - fail to read snapshot. key: {} reason: {}", key, e);
+                        None
+                    }
+                }
+            }
+            None => None,
+        }
+    }
+
+    // the snapshot key and topic list, or why it could not be had when no snapshot is cached either
+    fn revalidate(&mut self, latest: Option<(String, CacheEntry)>) -> Result<(String, Vec<u8>), String> {
         let time_format = |t: ::time::Tm| {
             match t.strftime("%Y%m%d%H%M") {
                 Ok(s) => s.to_string(),
@@ -28,29 +58,134 @@ impl<'a, T: 'a + Cache> Resource for IndexResource<'a, T> {
             }
         };
 
-        let time = time_format(::time::now());
+        let now = ::time::get_time().sec;
+        let url = self.url.clone();
 
-        let html_path = format!("data/cache/html/topics/");
-        let file_name = format!("{time}.html", time = time);
+        let (etag, last_modified) = match latest {
+            Some((_, ref entry)) => (entry.meta.etag.clone(), entry.meta.last_modified.clone()),
+            None => (None, None),
+        };
 
-        let (from_cache, result) = match self.cache.read(&html_path, &file_name) {
-            Ok(result) => (true, result),
-            Err(_) => {
-                let url = self.url;
-                let result = self.wr.get(&url);
-                (false, result.into_bytes())
+        match self.wr.fetch_conditional(&url, etag.as_ref(), last_modified.as_ref()) {
+            Ok(WebResponse::NotModified) => {
+                info!("index resource - not modified");
+                match latest {
+                    Some((key, entry)) => {
+                        let mut meta = entry.meta;
+                        meta.fetched_at = now;
+                        match self.cache.write_meta(&key, meta) {
+                            Ok(_) => {}
+                            Err(e) => error!("index resource - fail to write cache meta. reason: {}", e),
+                        }
+                        Ok((key, entry.body))
+                    }
+                    None => Err(String::from("not modified, but nothing cached")),
+                }
             }
-        };
+            Ok(WebResponse::Modified(page)) => {
+                // keep every changed topic list as a new snapshot
+                let key = topics_key(&time_format(::time::now()), &snapshot_suffix(self.backend, &self.channel));
+                let meta = CacheMeta {
+                    url: url.to_string(),
+                    content_type: Some(String::from(self.backend.content_type())),
+                    fetched_at: now,
+                    ttl: Some(TOPICS_TTL),
+                    etag: page.etag,
+                    last_modified: page.last_modified,
+                    ..Default::default()
+                };
+                let result = page.body.into_bytes();
+                // a cache that can not be written only costs the next visit
+                match self.cache.write(&key, result.clone(), meta) {
+                    Ok(_) => {}
+                    Err(e) => error!("index resource - fail to write cache. reason: {}", e),
+                }
+                Ok((key, result))
+            }
+            Err(e) => {
+                error!("index resource - fail to fetch. reason: {}", e);
+                match latest {
+                    // a stale topic list is better than nothing
+                    Some((key, entry)) => Ok((key, entry.body)),
+                    None => Err(e.to_string()),
+                }
+            }
+        }
+    }
+
+    fn index_item(&self, key: Option<String>, items: Vec<ListTopicItem>) -> ChannelIndexItem {
+        ChannelIndexItem {
+            forum: self.backend.forum().to_string(),
+            channel: self.channel.clone(),
+            key: key,
+            items: items
+        }
+    }
+
+    fn result_item(&mut self, key: String, body: Vec<u8>) -> ChannelItem {
+        match parse_topics_page(self.cache, self.backend, &body, &self.url) {
+            Ok(items) => {
+                ChannelItem {
+                    extra: Some( ChannelItemType::Index(self.index_item(Some(key), items)) ),
+                    result: Default::default(),
+                    unavailable: false,
+                }
+            }
+            Err(e) => {
+                error!("index resource - fail to parse topics. reason: {}", e);
+                ChannelItem {
+                    extra: Some( ChannelItemType::Index(self.index_item(None, Vec::new())) ),
+                    result: e.to_string(),
+                    unavailable: false,
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Resource for IndexResource<'a> {
+    fn fetch(&mut self, item: &ChannelItem) -> ChannelItem {
+        let latest = self.latest_snapshot();
 
-        if !from_cache {
-            let result2 = result.clone();
-            self.cache.write(&html_path, &file_name, result2).expect("fail to write cache");
+        if self.offline {
+            return match latest {
+                Some((key, entry)) => {
+                    self.cache.touch(&key).ok();
+                    self.result_item(key, entry.body)
+                },
+                None => {
+                    info!("index resource - no snapshot available offline");
+                    ChannelItem {
+                        extra: Some( ChannelItemType::Index(self.index_item(None, Vec::new())) ),
+                        result: Default::default(),
+                        unavailable: true,
+                    }
+                }
+            };
         }
 
-        let result_item = ChannelItem {
-            extra: Some( ChannelItemType::Index(ChannelIndexItem { }) ),
-            result: String::from_utf8(result).expect("fail to build result item, reason: invalid string"),
+        let is_fresh = match latest {
+            Some((_, ref entry)) => entry.meta.is_fresh(::time::get_time().sec),
+            None => false,
         };
-        result_item
+
+        let (key, result) = if is_fresh {
+            let (key, entry) = latest.unwrap();
+            self.cache.touch(&key).ok();
+            (key, entry.body)
+        } else {
+            match self.revalidate(latest) {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    return ChannelItem {
+                        extra: Some( ChannelItemType::Index(self.index_item(None, Vec::new())) ),
+                        result: e,
+                        unavailable: true,
+                    };
+                }
+            }
+        };
+
+        self.result_item(key, result)
     }
 }
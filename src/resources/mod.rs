@@ -4,35 +4,76 @@ pub mod show_resource;
 pub mod image_resource;
 pub mod default_resource;
 pub mod web_resource;
+pub mod history_resource;
+pub mod filter_resource;
+pub mod page_parser;
 
 use std::default::Default;
 
+use model::{ListTopicItem, ShowItem};
+use builders::common::ParseError;
+
 #[derive(Debug)]
 #[derive(Clone)]
 pub enum ChannelItemType {
     Show(ChannelShowItem),
     Index(ChannelIndexItem),
-    Image(ChannelImageItem)
+    Image(ChannelImageItem),
+    History(ChannelHistoryItem),
+    Filter(ChannelFilterItem)
 }
 
 #[derive(Debug)]
 #[derive(Clone)]
 #[derive(Default)]
-pub struct ChannelIndexItem { }
+pub struct ChannelIndexItem {
+    // empty forum and channel in requests mean the default board
+    pub forum: String,
+    pub channel: String,
+    // the snapshot the topics were read from, None in requests
+    pub key: Option<String>,
+    pub items: Vec<ListTopicItem>
+}
 
 #[derive(Debug)]
 #[derive(Clone)]
 #[derive(Default)]
 pub struct ChannelShowItem {
+    pub forum: String,
+    pub channel: String,
+    pub postid: String,
+    pub page: usize,
+    // None in requests and when the page failed to parse
+    pub show_item: Option<ShowItem>,
+    // why the page failed to parse, shown with the replies that failed
+    pub parse_error: Option<ParseError>
+}
+
+// the replies of one user across every page of a thread
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct ChannelFilterItem {
+    pub forum: String,
+    pub channel: String,
     pub postid: String,
+    pub userid: String,
+    // the last page read, results are sent while the walk goes on
     pub page: usize,
+    // pages of the thread, at least those the reader already knows of in requests
+    pub max_page: usize,
+    pub done: bool,
+    pub missing_pages: Vec<usize>,
+    pub show_item: Option<ShowItem>
 }
 
 #[derive(Debug)]
 #[derive(Default)]
 pub struct ChannelItem {
     pub extra: Option<ChannelItemType>,
-    pub result: String
+    // the reason a request failed, empty on success
+    pub result: String,
+    pub unavailable: bool
 }
 
 #[derive(Debug)]
@@ -43,3 +84,17 @@ pub struct ChannelImageItem {
     pub bytes: Vec<u8>,
     pub from_cache: bool
 }
+
+// a cached topic list snapshot, "key" is None for the newest one
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct ChannelHistoryItem {
+    pub forum: String,
+    pub channel: String,
+    pub key: Option<String>,
+    pub snapshots: Vec<String>,
+    pub items: Vec<ListTopicItem>,
+    // None for the oldest snapshot
+    pub previous: Option<Vec<ListTopicItem>>
+}
@@ -0,0 +1,46 @@
+use rustc_serialize::{Decodable, Encodable};
+
+use model::{ListTopicItem, ShowItem};
+use builders::common::*;
+use backends::Backend;
+use caches::common::*;
+use caches::model_cache::*;
+
+// pages are parsed on the worker threads, parsed pages are kept in the cache so showing a page again skips the parser
+fn parse_page<T, F>(cache: &mut dyn Cache, backend: &dyn Backend, kind: &str, body: &[u8], url: &str, parse: F) -> Result<T, ParseError>
+    where T: Encodable + Decodable, F: FnOnce(&[u8], &str) -> Result<(T, Vec<ParseError>), ParseError>
+{
+    let key = model_key(kind, body);
+
+    match read_model::<T>(cache, &key) {
+        Some(item) => return Ok(item),
+        None => {}
+    }
+
+    let (item, diagnostics) = match parse(body, url) {
+        Ok(result) => result,
+        Err(e) => {
+            error!("page parser - fail to parse {} with {}. url: {} reason: {}", kind, backend.name(), url, e);
+            return Err(e);
+        }
+    };
+
+    for e in &diagnostics {
+        error!("page parser - skipped part of {} with {}. url: {} reason: {}", kind, backend.name(), url, e);
+    }
+
+    match write_model(cache, &key, &item) {
+        Ok(_) => {}
+        Err(e) => error!("page parser - fail to cache {}. reason: {}", kind, e),
+    }
+
+    Ok(item)
+}
+
+pub fn parse_show_page(cache: &mut dyn Cache, backend: &dyn Backend, body: &[u8], url: &str) -> Result<ShowItem, ParseError> {
+    parse_page(cache, backend, "show", body, url, |body, url| backend.parse_show(body, url))
+}
+
+pub fn parse_topics_page(cache: &mut dyn Cache, backend: &dyn Backend, body: &[u8], url: &str) -> Result<Vec<ListTopicItem>, ParseError> {
+    parse_page(cache, backend, "topics", body, url, |body, url| backend.parse_topics(body, url))
+}
@@ -1,62 +1,192 @@
 use resources::*;
+use model::ShowItem;
 use resources::web_resource::*;
 use resources::common::*;
+use resources::page_parser::*;
 use caches::common::*;
+use caches::keys::*;
+use backends::Backend;
 
-pub struct ShowResource<'a, T: 'a + Cache> {
+// the last page of a thread keeps growing, so it is revalidated after a short while
+const LAST_PAGE_TTL: i64 = 60;
+
+pub struct ShowResource<'a> {
     wr: &'a mut WebResource,
-    cache: &'a mut Box<T>
+    cache: &'a mut dyn Cache,
+    backend: &'a dyn Backend,
+    channel: String,
+    offline: bool
 }
 
-impl<'a, T: 'a + Cache> ShowResource<'a, T> {
-    pub fn new(wr: &'a mut WebResource, cache: &'a mut Box<T>) -> Self {
+impl<'a> ShowResource<'a> {
+    pub fn new(wr: &'a mut WebResource, cache: &'a mut dyn Cache, backend: &'a dyn Backend, channel: &str, offline: bool) -> Self {
         ShowResource {
             wr: wr,
-            cache: cache            
+            cache: cache,
+            backend: backend,
+            channel: channel.to_string(),
+            offline: offline
         }
     }
-    fn post_url(&self, postid: &String, page: usize) -> String {
-        let base_url = "http://forum1.hkgolden.com/view.aspx";
-        let posturl = format!("{base_url}?type=BW&message={postid}&page={page}",
-                              base_url = base_url,
-                              postid = postid,
-                              page = page);
-        posturl
+
+    fn show_item(&self, postid: String, page: usize, show_item: Option<ShowItem>) -> ChannelShowItem {
+        ChannelShowItem {
+            forum: self.backend.forum().to_string(),
+            channel: self.channel.clone(),
+            postid: postid,
+            page: page,
+            show_item: show_item,
+            parse_error: None
+        }
     }
 
+    // a full page never changes again, only the last page needs revalidation
+    // the parsed page is kept in the cache, so showing it right after does not parse it again
+    fn ttl(&mut self, body: &[u8], posturl: &str) -> Option<i64> {
+        match parse_show_page(self.cache, self.backend, body, posturl) {
+            Ok(ref item) if item.page > 0 && item.page < item.max_page => None,
+            _ => Some(LAST_PAGE_TTL),
+        }
+    }
+
+    // only page 1 tells who started the thread on some forums, it is read from the cache or fetched once
+    fn thread_author(&mut self, postid: &str) -> Option<String> {
+        let key = show_key(postid, 1, self.backend.extension());
+        let posturl = self.backend.show_url(&self.channel, postid, 1);
+
+        let body = match self.cache.read(&key) {
+            Ok(entry) => entry.body,
+            Err(_) if self.offline => return None,
+            Err(_) => match self.revalidate(&key, &posturl, None) {
+                Ok(body) => body,
+                Err(_) => return None,
+            },
+        };
+
+        parse_show_page(self.cache, self.backend, &body, &posturl).ok().and_then(|x| x.author_userid)
+    }
+
+    // the page, or why it could not be had when nothing is cached either
+    fn revalidate(&mut self, key: &str, posturl: &str, cached: Option<CacheEntry>) -> Result<Vec<u8>, String> {
+        let now = ::time::get_time().sec;
+
+        let (etag, last_modified) = match cached {
+            Some(ref entry) => (entry.meta.etag.clone(), entry.meta.last_modified.clone()),
+            None => (None, None),
+        };
+
+        match self.wr.fetch_conditional(posturl, etag.as_ref(), last_modified.as_ref()) {
+            Ok(WebResponse::NotModified) => {
+                info!("show resource - not modified. url: {}", posturl);
+                match cached {
+                    Some(entry) => {
+                        let mut meta = entry.meta;
+                        meta.fetched_at = now;
+                        match self.cache.write_meta(key, meta) {
+                            Ok(_) => {}
+                            Err(e) => error!("show resource - fail to write cache meta. reason: {}", e),
+                        }
+                        Ok(entry.body)
+                    }
+                    None => Err(String::from("not modified, but nothing cached")),
+                }
+            }
+            Ok(WebResponse::Modified(page)) => {
+                let result = page.body.into_bytes();
+                let meta = CacheMeta {
+                    url: posturl.to_string(),
+                    content_type: Some(String::from(self.backend.content_type())),
+                    fetched_at: now,
+                    ttl: self.ttl(&result, posturl),
+                    etag: page.etag,
+                    last_modified: page.last_modified,
+                    ..Default::default()
+                };
+                match self.cache.write(key, result.clone(), meta) {
+                    Ok(_) => {}
+                    Err(e) => error!("show resource - fail to write cache. reason: {}", e),
+                }
+                Ok(result)
+            }
+            Err(e) => {
+                error!("show resource - fail to fetch. url: {} reason: {}", posturl, e);
+                match cached {
+                    // a stale page is better than nothing
+                    Some(entry) => Ok(entry.body),
+                    None => Err(e.to_string()),
+                }
+            }
+        }
+    }
 }
 
-impl<'a, T: 'a + Cache> Resource for ShowResource<'a, T> {
+impl<'a> Resource for ShowResource<'a> {
     fn fetch(&mut self, item: &ChannelItem) -> ChannelItem {
         info!("show resource #fetch");
         match item.extra.clone() {
             Some(o) => {
                 match o {
                     ChannelItemType::Show(extra) => {
-                        let html_path = format!("data/cache/html/{postid}/", postid = extra.postid);
-                        let show_file_name = format!("show_{page}.html", page = extra.page);
+                        let key = show_key(&extra.postid, extra.page, self.backend.extension());
 
                         let postid = extra.postid.clone();
 
-                        let (from_cache, result) = match self.cache.read(&html_path, &show_file_name) {
-                            Ok(result) => (true, result),
-                            Err(_) => {
-                                let posturl = self.post_url(&extra.postid, extra.page);
-                                let result = self.wr.get(&posturl);
-                                (false, result.into_bytes())
-                            }
-                        };
+                        let cached = self.cache.read(&key).ok();
 
-                        if !from_cache {
-                            let result2 = result.clone();
-                            self.cache.write(&html_path, &show_file_name, result2).expect("fail to write cache");
+                        if cached.is_none() && self.offline {
+                            info!("show resource - page not available offline. postid: {} page: {}", postid, extra.page);
+                            return ChannelItem {
+                                extra: Some(ChannelItemType::Show(self.show_item(postid, extra.page, None))),
+                                result: Default::default(),
+                                unavailable: true,
+                            };
                         }
 
-                        let result_item = ChannelItem {
-                            extra: Some(ChannelItemType::Show(ChannelShowItem { postid: postid, page: extra.page })),
-                            result: String::from_utf8(result).expect("fail to build result item, reason: invalid string"),
+                        let is_fresh = match cached {
+                            Some(ref entry) => self.offline || entry.meta.is_fresh(::time::get_time().sec),
+                            None => false,
+                        };
+
+                        let posturl = self.backend.show_url(&self.channel, &extra.postid, extra.page);
+
+                        let result = if is_fresh {
+                            self.cache.touch(&key).ok();
+                            cached.unwrap().body
+                        } else {
+                            match self.revalidate(&key, &posturl, cached) {
+                                Ok(body) => body,
+                                Err(e) => {
+                                    return ChannelItem {
+                                        extra: Some(ChannelItemType::Show(self.show_item(postid, extra.page, None))),
+                                        result: e,
+                                        unavailable: true,
+                                    };
+                                }
+                            }
+                        };
+
+                        let (show_item, error) = match parse_show_page(self.cache, self.backend, &result, &posturl) {
+                            Ok(mut show_item) => {
+                                if show_item.author_userid.is_none() && extra.page > 1 {
+                                    let author_userid = self.thread_author(&postid);
+                                    show_item.set_author(author_userid);
+                                }
+                                (Some(show_item), None)
+                            }
+                            Err(e) => {
+                                error!("show resource - fail to parse page. postid: {} page: {} reason: {}", postid, extra.page, e);
+                                (None, Some(e))
+                            }
                         };
-                        result_item
+
+                        let mut show = self.show_item(postid, extra.page, show_item);
+                        let reason = error.as_ref().map(|e| e.to_string()).unwrap_or_default();
+                        show.parse_error = error;
+                        ChannelItem {
+                            extra: Some(ChannelItemType::Show(show)),
+                            result: reason,
+                            unavailable: false,
+                        }
                     },
                     _ => Default::default()
                 }
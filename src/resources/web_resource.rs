@@ -5,6 +5,18 @@ use std::collections::HashMap;
 use std::io::{Error, ErrorKind};
 
 use ::hyper::header::{Headers, UserAgent};
+use ::hyper::status::StatusCode;
+
+pub enum WebResponse {
+    Modified(WebPage),
+    NotModified
+}
+
+pub struct WebPage {
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>
+}
 
 pub struct WebResource {
      pub pages: HashMap<String, String>,
@@ -51,6 +63,39 @@ impl WebResource {
         }
     }
 
+    pub fn fetch_conditional(&mut self, url: &str, etag: Option<&String>, last_modified: Option<&String>) -> Result<WebResponse, Error> {
+        info!("web resource #fetch_conditional");
+        let mut headers = Headers::new();
+        headers.set(UserAgent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_12_3) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/56.0.2924.87 Safari/537.36".to_owned()));
+        if let Some(etag) = etag {
+            headers.set_raw("If-None-Match", vec![etag.clone().into_bytes()]);
+        }
+        if let Some(last_modified) = last_modified {
+            headers.set_raw("If-Modified-Since", vec![last_modified.clone().into_bytes()]);
+        }
+        let mut client = Client::new();
+        client.set_read_timeout(Some(::std::time::Duration::from_secs(5)));
+        client.set_write_timeout(Some(::std::time::Duration::from_secs(5)));
+        match client.get(url).headers(headers).send() {
+            Ok(mut resp) => {
+                if resp.status == StatusCode::NotModified {
+                    return Ok(WebResponse::NotModified);
+                }
+                if resp.status != StatusCode::Ok {
+                    return Err(Error::new(ErrorKind::InvalidData, format!("http status {}", resp.status)));
+                }
+                let etag = raw_header(&resp.headers, "ETag");
+                let last_modified = raw_header(&resp.headers, "Last-Modified");
+                let mut s = String::new();
+                match resp.read_to_string(&mut s) {
+                    Ok(_) => Ok(WebResponse::Modified(WebPage { body: s, etag: etag, last_modified: last_modified })),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(Error::new(ErrorKind::InvalidData, e)),
+        }
+    }
+
     pub fn fetch_safe(&mut self, url: &str) -> String {
         match self.fetch(&String::from(url)) {
             Ok(s) => s,
@@ -73,3 +118,9 @@ impl WebResource {
         self.find(url)
     }
 }
+
+fn raw_header(headers: &Headers, name: &str) -> Option<String> {
+    headers.get_raw(name)
+           .and_then(|values| values.first())
+           .and_then(|value| String::from_utf8(value.clone()).ok())
+}
@@ -1,9 +1,17 @@
 use std::sync::mpsc::Sender;
-use ::kuchiki::traits::*;
 
 use status::*;
 use state_manager::*;
 use resources::*;
+use model::{ShowItem, ShowReplyItem, UrlQueryItem};
+use reply_model::{NodeType, TextNode};
+use caches::keys::*;
+use history::*;
+use caches::eviction::{PinnedKeys, pin_keys};
+use resources::filter_resource::is_walking;
+
+const OFFLINE_NOTICE: &'static str = "沒有離線快取 (not available offline)";
+const UNPARSED_NOTICE: &'static str = "未能解析 (could not parse), d 查看 (d for details)";
 
 pub struct Responser {}
 
@@ -17,10 +25,20 @@ impl Responser {
                 match item.extra {
                     Some(o) => {
                         match o {
-                            ChannelItemType::Show(extra) => {
-                                let document = ::kuchiki::parse_html().from_utf8().one(item.result.as_bytes());
-
-                                let posturl = get_posturl(&extra.postid, extra.page);
+                            ChannelItemType::Show(mut extra) => {
+                                if item.unavailable {
+                                    app.status_bar.append(&app.screen_manager,
+                                                          &format!("[{}-{}:OFFLINE]", extra.postid, extra.page));
+                                    app.show_item = notice_show_item(&extra, &app.show_item, &unavailable_notice(&item.result));
+                                    app.show.set_filter(None);
+                                    app.show.set_page_error(None);
+                                    app.show.reset_y();
+                                    ::screen::common::clear_screen();
+                                    app.state_manager.update_state(Status::Show);
+                                    app.state_manager.set_to_print_screen(true);
+                                    app.state_manager.set_web_request(false);
+                                    return;
+                                }
 
                                 app.status_bar.append(&app.screen_manager,
                                                       &format!("[{}-{}:ROK][{}]",
@@ -28,70 +46,73 @@ impl Responser {
                                                                app.show_item.page,
                                                                app.state_manager.is_web_request()));
 
-                                match app.show_builder.build(&document, &posturl) {
-                                    Ok(item) => {
-                                        app.show_item = item;
-
-                                        // get all images links in an array, and send to background download
-                                        let maps = app.show_item.replies.iter().flat_map(|reply| {
-                                                let f = reply.body.iter().filter(|node| {
-                                                        let node2 = node.clone();
-                                                        match *node2 {
-                                                            ::reply_model::NodeType::Image(ref n) => {
-                                                                (n.data.starts_with("http") || n.data.starts_with("https")) && n.alt.starts_with("[img]") && n.alt.ends_with("[/img]")
-                                                            }
-                                                            _ => false,
-                                                        }
-                                                    }).collect::<Vec<_>>();
-                                                f
-                                            })
-                                            .collect::<Vec<_>>();
-
-                                        let count = maps.len();
-                                        app.status_bar.append(&app.screen_manager,
-                                                              &format!("[SIMG:{count}]", count = count));
-
-                                        for node in &maps {
-                                            let node2 = node.clone();
-                                            match *node2 {
-                                                ::reply_model::NodeType::Image(ref n) => {
-                                                    let status_message = image_request(&n.data, &mut app.state_manager, &app.tx_req);
-                                                    app.status_bar.append(&app.screen_manager, &status_message);
-                                                }
-                                                _ => {}
-                                            }
+                                // pages arrive already parsed, only the model is swapped here
+                                match extra.show_item.take() {
+                                    Some(mut show_item) => {
+                                        // only page 1 tells who started the thread, keep it while paging through
+                                        if show_item.author_userid.is_none() && show_item.url_query.message == app.show_item.url_query.message {
+                                            show_item.set_author(app.show_item.author_userid.clone());
                                         }
+                                        app.show_item = show_item;
+                                        app.show.set_filter(None);
+                                        app.show.set_page_error(None);
+
+                                        let key = show_key(&extra.postid, extra.page, app.backends.get(&extra.forum).extension());
+                                        request_images(app, vec![key]);
 
                                         app.show.reset_y();
                                         ::screen::common::clear_screen();
                                         app.state_manager.update_state(Status::Show); //state = Status::Show;
                                     },
-                                    Err(e) => {
-                                        error!("show item failed to build. reason: {:?}", e);
+                                    None => {
+                                        error!("show item failed to build. reason: {}", item.result);
                                         app.status_bar.append(&app.screen_manager, &"[SPFAIL]");
+
+                                        // the page stays open with why it failed, for the d view
+                                        match extra.parse_error.take() {
+                                            Some(e) => {
+                                                app.show_item = notice_show_item(&extra, &app.show_item, UNPARSED_NOTICE);
+                                                app.show.set_filter(None);
+                                                app.show.set_page_error(Some(e));
+                                                app.show.reset_y();
+                                                ::screen::common::clear_screen();
+                                                app.state_manager.update_state(Status::Show);
+                                            }
+                                            None => {}
+                                        }
                                     }
                                 };
                                 app.state_manager.set_to_print_screen(true);
                                 app.state_manager.set_web_request(false); // is_web_requesting = false;
                             }
-                            ChannelItemType::Index(_) => {
-                                let document = ::kuchiki::parse_html().from_utf8().one(item.result.as_bytes());
-
+                            ChannelItemType::Index(extra) => {
                                 app.list_topic_items.clear();
+                                pin_keys(&app.pinned_keys, "list", extra.key.iter().cloned().collect());
 
-                                match app.index_builder.build(&document) {
-                                    Ok(items) => {
-                                        for item in items {
-                                            app.list_topic_items.push(item);
-                                        }
+                                if item.unavailable {
+                                    app.status_bar.append(&app.screen_manager, &format!("[TOPICS:OFFLINE]"));
+                                    app.index.set_notice(Some(unavailable_notice(&item.result)));
+                                    ::screen::common::clear_screen();
+                                    app.state_manager.update_state(Status::List);
+                                    app.state_manager.set_to_print_screen(true);
+                                    app.state_manager.set_web_request(false);
+                                    return;
+                                }
+
+                                app.index.set_notice(None);
+                                app.index.set_history(None);
+
+                                match item.result.is_empty() {
+                                    true => {
+                                        app.list_topic_items = extra.items;
 
                                         app.status_bar.append(&app.screen_manager, &format!("[TOPICS:ROK]"));
 
                                         ::screen::common::clear_screen();
                                         app.state_manager.update_state(Status::List); // state = Status::List;
                                     },
-                                    Err(e) => {
-                                        error!("index item failed to build. reason: {:?}", e);
+                                    false => {
+                                        error!("index item failed to build. reason: {}", item.result);
                                         app.status_bar.append(&app.screen_manager, &"[IPFAIL]");
                                     }
                                 }
@@ -99,6 +120,124 @@ impl Responser {
                                 app.state_manager.set_web_request(false); // is_web_requesting = false;
 
                             }
+                            ChannelItemType::History(extra) => {
+                                // the snapshot shown and the one it is compared with
+                                let previous_key = extra.key.as_ref()
+                                                            .and_then(|key| extra.snapshots.iter().position(|x| x == key))
+                                                            .and_then(|i| if i > 0 { extra.snapshots.get(i - 1).cloned() } else { None });
+                                pin_keys(&app.pinned_keys, "list", extra.key.iter().cloned().chain(previous_key).collect());
+
+                                if item.unavailable {
+                                    app.status_bar.append(&app.screen_manager, "[HISTORY:EMPTY]");
+                                    app.state_manager.set_web_request(false);
+                                    return;
+                                }
+
+                                match item.result.is_empty() {
+                                    true => {
+                                        let items = extra.items;
+                                        let (mut marks, mut disappeared) = match extra.previous {
+                                            Some(ref previous_items) => compare(previous_items, &items),
+                                            None => (items.iter().map(|_| TopicMark::Unchanged).collect::<Vec<_>>(), Vec::new()),
+                                        };
+
+                                        // topics gone since the previous snapshot are listed at the end
+                                        marks.extend(disappeared.iter().map(|_| TopicMark::Disappeared));
+
+                                        app.list_topic_items = items;
+                                        app.list_topic_items.append(&mut disappeared);
+
+                                        app.index.set_notice(None);
+                                        app.index.set_history(Some(HistoryView {
+                                            key: extra.key.unwrap_or_default(),
+                                            snapshots: extra.snapshots,
+                                            marks: marks
+                                        }));
+
+                                        app.status_bar.append(&app.screen_manager, "[HISTORY:ROK]");
+
+                                        ::screen::common::clear_screen();
+                                        app.state_manager.update_state(Status::List);
+                                    },
+                                    false => {
+                                        error!("history item failed to build. reason: {}", item.result);
+                                        app.status_bar.append(&app.screen_manager, &"[HPFAIL]");
+                                    }
+                                }
+                                app.state_manager.set_to_print_screen(true);
+                                app.state_manager.set_web_request(false);
+                            }
+                            ChannelItemType::Filter(mut extra) => {
+                                // the reader may have left the thread, or gone back to its pages, while the walk went on
+                                if !is_walking(&app.filter_walk) || app.state_manager.get_state() != Status::Show ||
+                                   app.show_item.url_query.message != extra.postid {
+                                    if extra.done {
+                                        app.state_manager.set_web_request(false);
+                                    }
+                                    return;
+                                }
+
+                                if item.unavailable {
+                                    app.status_bar.append(&app.screen_manager, &format!("[FILTER-{}:OFFLINE]", extra.postid));
+                                    app.state_manager.set_to_print_screen(true);
+                                    app.state_manager.set_web_request(false);
+                                    return;
+                                }
+
+                                // partial results come in while the pages are walked, keep the reader's place among them
+                                let is_shown = app.show.get_filter().is_some();
+
+                                match extra.show_item.take() {
+                                    Some(show_item) => {
+                                        let label = if show_item.author_userid.as_ref() == Some(&extra.userid) {
+                                            String::from("只看樓主")
+                                        } else {
+                                            let name = show_item.replies.first().map(|x| x.username.clone()).unwrap_or_default();
+                                            format!("只看 {}", name)
+                                        };
+                                        app.show_item = show_item;
+                                        app.show.set_filter(Some(label));
+                                        app.show.set_page_error(None);
+
+                                        if !is_shown {
+                                            app.show.reset_y();
+                                        }
+
+                                        if !extra.done {
+                                            app.status_bar.append(&app.screen_manager,
+                                                                  &format!("[FILTER-{}:{}/{}]", extra.postid, extra.page, extra.max_page));
+                                            if app.state_manager.get_state() == Status::Show {
+                                                ::screen::common::clear_screen();
+                                                app.state_manager.set_to_print_screen(true);
+                                            }
+                                            return;
+                                        }
+                                        ::screen::common::clear_screen();
+
+                                        app.status_bar.append(&app.screen_manager,
+                                                              &format!("[FILTER-{}:{}/{}]", extra.postid, app.show_item.replies.len(), extra.max_page));
+                                        if !extra.missing_pages.is_empty() {
+                                            app.status_bar.append(&app.screen_manager,
+                                                                  &format!("[FILTER:MISS-{}]", extra.missing_pages.len()));
+                                        }
+
+                                        let backend = app.backends.get(&extra.forum);
+                                        let keys = (1..extra.max_page + 1).filter(|x| !extra.missing_pages.contains(x))
+                                                                          .map(|x| show_key(&extra.postid, x, backend.extension()))
+                                                                          .collect::<Vec<_>>();
+                                        request_images(app, keys);
+                                    }
+                                    None => {
+                                        if !extra.done {
+                                            return;
+                                        }
+                                        error!("filter item failed to build. reason: {}", item.result);
+                                        app.status_bar.append(&app.screen_manager, &"[FPFAIL]");
+                                    }
+                                }
+                                app.state_manager.set_to_print_screen(true);
+                                app.state_manager.set_web_request(false);
+                            }
                             ChannelItemType::Image(extra) => {
                                 if item.result != "" {
                                     error!("image item failed to build.");
@@ -119,15 +258,113 @@ impl Responser {
 
 }
 
-fn get_posturl(postid: &String, page: usize) -> String {
-    let base_url = "http://forum1.hkgolden.com/view.aspx";
-    let posturl = format!("{base_url}?type=BW&message={postid}&page={page}",
-                          base_url = base_url,
-                          postid = postid,
-                          page = page);
-    posturl
+// linked images of a reply body, including those inside styled spans, links, quotes, lists and tables
+fn collect_images<'a>(nodes: &'a Vec<NodeType>, images: &mut Vec<&'a NodeType>) {
+    for node in nodes {
+        match *node {
+            NodeType::Image(ref n) => {
+                if n.is_posted() {
+                    images.push(node);
+                }
+            }
+            NodeType::Span(ref n) => collect_images(&n.data, images),
+            NodeType::Link(ref n) => collect_images(&n.data, images),
+            NodeType::BlockQuote(ref n) => collect_images(&n.data, images),
+            NodeType::List(ref n) => {
+                for item in &n.items {
+                    collect_images(item, images);
+                }
+            }
+            NodeType::Table(ref n) => {
+                for row in &n.rows {
+                    for cell in row {
+                        collect_images(&cell.data, images);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
+// get all images links of the replies on screen, and send them to background download
+fn request_images(app: &mut ::App, page_keys: Vec<String>) {
+    let mut maps = Vec::new();
+    for reply in &app.show_item.replies {
+        collect_images(&reply.body, &mut maps);
+    }
+
+    pin_show_item(&app.pinned_keys, page_keys, &maps);
+
+    let unparsed_count = app.show_item.replies.iter().filter(|x| x.diagnostic.is_some()).count();
+    if unparsed_count > 0 {
+        app.status_bar.append(&app.screen_manager,
+                              &format!("[SPERR:{count}]", count = unparsed_count));
+    }
+
+    let count = maps.len();
+    app.status_bar.append(&app.screen_manager,
+                          &format!("[SIMG:{count}]", count = count));
+
+    for node in &maps {
+        match **node {
+            NodeType::Image(ref n) => {
+                let status_message = image_request(&n.data, &mut app.state_manager, &app.tx_req);
+                app.status_bar.append(&app.screen_manager, &status_message);
+            }
+            _ => {}
+        }
+    }
+}
+
+// keep the pages and their images from being evicted while they are on screen
+fn pin_show_item(pinned_keys: &PinnedKeys, page_keys: Vec<String>, nodes: &Vec<&NodeType>) {
+    let mut keys = page_keys;
+    for node in nodes {
+        match **node {
+            NodeType::Image(ref n) => keys.push(image_key(&n.data)),
+            _ => {}
+        }
+    }
+    pin_keys(pinned_keys, "show", keys);
+}
+
+// offline, or why the network failed when nothing was cached
+fn unavailable_notice(reason: &str) -> String {
+    if reason.is_empty() {
+        String::from(OFFLINE_NOTICE)
+    } else {
+        format!("未能下載 (fail to download): {}", reason)
+    }
+}
+
+// placeholder page for a thread page that could not be shown, so the reader can still page around it
+fn notice_show_item(extra: &ChannelShowItem, current: &ShowItem, notice: &str) -> ShowItem {
+    let max_page = if current.url_query.message == extra.postid && current.max_page > extra.page {
+        current.max_page
+    } else {
+        extra.page
+    };
+
+    ShowItem {
+        url_query: UrlQueryItem {
+            forum: extra.forum.clone(),
+            channel: extra.channel.clone(),
+            message: extra.postid.clone()
+        },
+        title: notice.to_string(),
+        reply_count: Default::default(),
+        page: extra.page,
+        max_page: max_page,
+        replies: vec![
+            ShowReplyItem {
+                body: vec![NodeType::Text(TextNode { data: notice.to_string() })],
+                ..Default::default()
+            }
+        ],
+        author_userid: if current.url_query.message == extra.postid { current.author_userid.clone() } else { None }
+    }
+}
 
 fn image_request(url: &String, state_manager: &mut StateManager, tx_req: &Sender<ChannelItem>) -> String {
 
@@ -141,6 +378,7 @@ fn image_request(url: &String, state_manager: &mut StateManager, tx_req: &Sender
                                   from_cache: Default::default()
                               })),
         result: Default::default(),
+        unavailable: false,
     };
 
     let status_message = match tx_req.send(ci) {
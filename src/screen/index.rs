@@ -3,10 +3,15 @@ use std;
 
 use utility::string::*;
 use model::ListTopicItem;
+use history::*;
+use backends::ForumChannel;
 
 pub struct Index {
     title: String,
     selected_topic_index: usize,
+    notice: Option<String>,
+    history: Option<HistoryView>,
+    channel_picker: Option<Vec<ForumChannel>>,
 }
 
 impl Index {
@@ -14,9 +19,41 @@ impl Index {
         Index {
             title: String::from("高登"),
             selected_topic_index: 0,
+            notice: None,
+            history: None,
+            channel_picker: None,
         }
     }
 
+    pub fn set_title(&mut self, title: &str) {
+        self.title = String::from(title);
+    }
+
+    pub fn open_channel_picker(&mut self, channels: Vec<ForumChannel>) {
+        self.channel_picker = Some(channels);
+    }
+
+    pub fn close_channel_picker(&mut self) {
+        self.channel_picker = None;
+    }
+
+    pub fn get_channel_picker(&self) -> Option<&Vec<ForumChannel>> {
+        self.channel_picker.as_ref()
+    }
+
+    // browsing a cached snapshot instead of the live topic list
+    pub fn set_history(&mut self, history: Option<HistoryView>) {
+        self.history = history;
+    }
+
+    pub fn get_history(&self) -> Option<&HistoryView> {
+        self.history.as_ref()
+    }
+
+    pub fn set_notice(&mut self, notice: Option<String>) {
+        self.notice = notice;
+    }
+
     pub fn select_topic(&mut self, index: usize) {
         self.selected_topic_index = index;
     }
@@ -33,13 +70,62 @@ impl Index {
 
         let width = ::termion::terminal_size().expect("fail to get terminal size").0 as usize;
 
-        print_header(stdout, width as usize, &self.title);
+        let title = match self.history {
+            Some(ref history) => format!("{} - {}", self.title, history.label()),
+            None => self.title.clone(),
+        };
+
+        print_header(stdout, width as usize, &title);
         print_body(stdout,
                    self.body_width(),
                    2,
                    self.body_height(),
                    &collection,
-                   self.selected_topic_index);
+                   self.selected_topic_index,
+                   self.history.as_ref());
+
+        if collection.is_empty() {
+            match self.notice {
+                Some(ref notice) => print_notice(stdout, width, 3, notice),
+                None => {}
+            }
+        }
+
+        if self.channel_picker.is_some() {
+            self.print_channel_picker(stdout);
+        }
+    }
+
+    fn print_channel_picker(&mut self, stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>) {
+        let channels = match self.channel_picker {
+            Some(ref channels) => channels.clone(),
+            None => return,
+        };
+
+        let width = if self.body_width() > 2 { self.body_width() - 2 } else { 0 };
+        let title = " 頻道 (channels) ";
+        let footer = " 1-9 切換 (switch)  Esc 關閉 (close) ";
+        let border = |text: &str, corner: &str| {
+            let fill = if width > jks_len(text) { width - jks_len(text) } else { 0 };
+            format!("{}{}{}", corner, text, seq_str_gen(0, fill, "─", ""))
+        };
+
+        let mut lines = vec![border(title, "┌")];
+        for (i, channel) in channels.iter().take(9).enumerate() {
+            let label = format!("{}. {} [{}/{}]", i + 1, channel.name, channel.forum, channel.channel);
+            lines.push(format!("│ {}", if width > 4 { substring(&label, width - 2) } else { label }));
+        }
+        lines.push(border(footer, "└"));
+
+        for (i, line) in lines.iter().enumerate() {
+            write!(stdout, "{}{}{}{}{}{}",
+                    ::termion::cursor::Goto(1, (i + 3) as u16),
+                    ::termion::clear::CurrentLine,
+                    ::termion::color::Fg(::termion::color::Cyan),
+                    format!(" {}", line),
+                    ::termion::style::Reset,
+                    ::termion::cursor::Hide).expect("fail to write to shell");
+        }
     }
 
     pub fn body_height(&self) -> usize {
@@ -103,12 +189,25 @@ fn print_header(stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>, w
             ::termion::cursor::Hide).expect("fail to write to shell");;
 }
 
+fn print_notice(stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>, width: usize, y: usize, text: &str) {
+    let text_len = jks_len(text);
+    let padding = (if width > text_len { width - text_len } else { 0 }) / 2;
+
+    write!(stdout, "{}{}{}{}{}",
+            ::termion::cursor::Goto((padding + 1) as u16, y as u16),
+            ::termion::color::Fg(::termion::color::Yellow),
+            text,
+            ::termion::style::Reset,
+            ::termion::cursor::Hide).expect("fail to write to shell");
+}
+
 fn print_body(stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>,
     width: usize,
     offset_y: usize,
     rows: usize,
     collection: &Vec<ListTopicItem>,
-    selected_topic_index: usize){
+    selected_topic_index: usize,
+    history: Option<&HistoryView>){
 
     let right_offset = 3;
     let author_max_width = 12;
@@ -117,7 +216,14 @@ fn print_body(stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>,
 
     for (i, item) in collection.iter().take(rows).enumerate() {
 
-        let original_title = item.title.text.clone();
+        let mark = history.map_or(TopicMark::Unchanged, |x| x.mark(i));
+
+        let original_title = match mark {
+            TopicMark::Unchanged => item.title.text.clone(),
+            TopicMark::Appeared => format!("[+] {}", item.title.text),
+            TopicMark::Disappeared => format!("[-] {}", item.title.text),
+            TopicMark::Jumped(n) => format!("[↑{}] {}", n, item.title.text),
+        };
         let title: String = substring(&original_title, title_max_width);
         let title_len = jks_len(&title);
 
@@ -148,9 +254,15 @@ fn print_body(stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>,
                       ::termion::cursor::Hide).expect("fail to write to shell");
 
         } else {
+             let color = match mark {
+                 TopicMark::Unchanged => format!("{}", ::termion::color::Fg(::termion::color::White)),
+                 TopicMark::Appeared => format!("{}", ::termion::color::Fg(::termion::color::Green)),
+                 TopicMark::Disappeared => format!("{}", ::termion::color::Fg(::termion::color::Red)),
+                 TopicMark::Jumped(_) => format!("{}", ::termion::color::Fg(::termion::color::Cyan)),
+             };
              write!(stdout, "{}{}{}{}{}",
                      ::termion::cursor::Goto(1, (i + offset_y + 1) as u16),
-                     ::termion::color::Fg(::termion::color::White),
+                     color,
                      format!("[{no:0>2}] {title}{title_spacing}| {author}{author_spacing}",
                               no = i + 1,
                               title = title,
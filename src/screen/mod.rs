@@ -2,3 +2,4 @@ pub mod common;
 pub mod status_bar;
 pub mod index;
 pub mod show;
+pub mod style;
@@ -1,13 +1,17 @@
 use std::io::Write;
-use std;
 
 use chrono::*;
 use utility::string::*;
-use model::IconItem;
 use model::ShowReplyItem;
+use model::Gender;
 use model::ShowItem;
 use reply_model::*;
 use screen::common::*;
+use screen::style::*;
+use caches::shared_cache::SharedCache;
+use icons::{Icon, IconRegistry};
+use utility::time::DisplayZone;
+use builders::common::ParseError;
 
 pub struct Show {
     title: String,
@@ -16,11 +20,31 @@ pub struct Show {
     replier_max_width: usize,
     time_max_width: usize,
     is_scroll_to_end: bool,
-    icon_collection: Box<Vec<IconItem>>
+    icons: IconRegistry,
+    inline_images: bool,
+    display_zone: DisplayZone,
+    cache: SharedCache,
+    color_support: ColorSupport,
+    hyperlinks: bool,
+    // links are numbered per reply, in reading order
+    link_number: usize,
+    // y of every reply on the last print, to tell which one is at the top
+    reply_offsets: Vec<usize>,
+    link_picker: Option<Vec<LinkNode>>,
+    // replies that failed to parse, listed over the thread
+    diagnostics_view: bool,
+    // the page itself failed to parse, listed before the replies
+    page_error: Option<ParseError>,
+    // label of the reply filter, the replies then cover the whole thread instead of a page
+    filter: Option<String>,
+    // markers of the list items being printed, and whether their first line is still to come
+    indents: Vec<(String, bool)>,
+    // columns and rows to print in, the terminal's when not given
+    size: Option<(u16, u16)>
 }
 
 impl Show {
-    pub fn new (icon_collection: Box<Vec<IconItem>>) -> Self {
+    pub fn new (icons: IconRegistry, inline_images: bool, display_zone: DisplayZone, cache: SharedCache) -> Self {
         Show {
             title: String::from("高登"),
             scroll_y: 0,
@@ -28,37 +52,200 @@ impl Show {
             replier_max_width: 14,
             time_max_width: 5,
             is_scroll_to_end: false,
-            icon_collection: icon_collection
+            icons: icons,
+            inline_images: inline_images,
+            display_zone: display_zone,
+            cache: cache,
+            color_support: color_support(),
+            hyperlinks: hyperlink_support(),
+            link_number: 0,
+            reply_offsets: Vec::new(),
+            link_picker: None,
+            diagnostics_view: false,
+            page_error: None,
+            filter: None,
+            indents: Vec::new(),
+            size: None
         }
     }
-    pub fn print(&mut self, stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>, item: &ShowItem) {
+
+    // prints as if the terminal had this size, for output that is not the terminal
+    pub fn with_size(mut self, width: u16, height: u16) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    fn terminal_size(&self) -> (u16, u16) {
+        match self.size {
+            Some(size) => size,
+            None => ::termion::terminal_size().expect("fail to get terminal size"),
+        }
+    }
+
+    pub fn open_link_picker(&mut self, links: Vec<LinkNode>) {
+        self.link_picker = Some(links);
+    }
+
+    pub fn close_link_picker(&mut self) {
+        self.link_picker = None;
+    }
+
+    pub fn get_link_picker(&self) -> Option<&Vec<LinkNode>> {
+        self.link_picker.as_ref()
+    }
+
+    pub fn set_diagnostics_view(&mut self, diagnostics_view: bool) {
+        self.diagnostics_view = diagnostics_view;
+    }
+
+    pub fn is_diagnostics_view(&self) -> bool {
+        self.diagnostics_view
+    }
+
+    pub fn set_page_error(&mut self, page_error: Option<ParseError>) {
+        self.page_error = page_error;
+    }
+
+    pub fn get_page_error(&self) -> Option<&ParseError> {
+        self.page_error.as_ref()
+    }
+
+    pub fn set_filter(&mut self, filter: Option<String>) {
+        self.filter = filter;
+    }
+
+    pub fn get_filter(&self) -> Option<&String> {
+        self.filter.as_ref()
+    }
+
+    // the reply at the top of the screen
+    pub fn current_reply(&self) -> usize {
+        let top = self.scroll_y + 2;
+        self.reply_offsets.iter().rposition(|&y| y <= top).unwrap_or(0)
+    }
+    pub fn print<W: Write>(&mut self, stdout: &mut W, item: &ShowItem) {
 
         self.y = 2;
         let title = self.title.clone();
-        self.print_header(stdout, &format!("{} - {} [{}/{}]",
-                                   item.title,
-                                   title,
-                                   item.page,
-                                   item.max_page));
+        let header = match self.filter {
+            Some(ref filter) => format!("{} - {} [{}:{}]", item.title, title, filter, item.replies.len()),
+            None => format!("{} - {} [{}/{}]", item.title, title, item.page, item.max_page),
+        };
+        self.print_header(stdout, &header);
         self.print_body(stdout, &item);
+
+        if self.link_picker.is_some() {
+            self.print_link_picker(stdout);
+        }
+
+        if self.diagnostics_view {
+            self.print_diagnostics(stdout, item);
+        }
     }
 
-    fn print_separator_top(&mut self, stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>, reply: &ShowReplyItem) {
+    fn print_diagnostics<W: Write>(&mut self, stdout: &mut W, item: &ShowItem) {
+        let width = if self.body_width() > 2 { self.body_width() - 2 } else { 0 };
+        let title = " 解析錯誤 (parse errors) ";
+        let footer = " Esc 關閉 (close) ";
+        let border = |text: &str, corner: &str| {
+            let fill = if width > jks_len(text) { width - jks_len(text) } else { 0 };
+            format!("{}{}{}", corner, text, seq_str_gen(0, fill, "─", ""))
+        };
+
+        let mut details = Vec::new();
+        let mut push_details = |label: String, e: &ParseError| {
+            details.push(format!("{} {}", label, e.message));
+            if let Some(ref selector) = e.selector {
+                details.push(format!("   selector: {}", selector));
+            }
+            if let Some(ref snippet) = e.snippet {
+                details.push(format!("   html: {}", snippet.split_whitespace().collect::<Vec<_>>().join(" ")));
+            }
+        };
+        match self.page_error {
+            Some(ref e) => push_details(String::from("頁 (page)"), e),
+            None => {}
+        }
+        for (i, reply) in item.replies.iter().enumerate() {
+            match reply.diagnostic {
+                Some(ref e) => push_details(format!("#{}", e.index.unwrap_or(i)), e),
+                None => {}
+            }
+        }
+
+        // the borders take two rows
+        let rows = if self.body_height() > 2 { self.body_height() - 2 } else { 0 };
+        let mut lines = vec![border(title, "┌")];
+        for detail in details.iter().take(rows) {
+            lines.push(format!("│ {}", if width > 4 { substring(detail, width - 2) } else { detail.clone() }));
+        }
+        lines.push(border(footer, "└"));
+
+        for (i, line) in lines.iter().enumerate() {
+            write!(stdout, "{}{}{}{}{}{}",
+                    ::termion::cursor::Goto(1, (i + 3) as u16),
+                    ::termion::clear::CurrentLine,
+                    ::termion::color::Fg(::termion::color::Yellow),
+                    format!(" {}", line),
+                    ::termion::style::Reset,
+                    ::termion::cursor::Hide).expect("fail to write to shell");
+        }
+    }
+
+    fn print_link_picker<W: Write>(&mut self, stdout: &mut W) {
+        let links = match self.link_picker {
+            Some(ref links) => links.clone(),
+            None => return,
+        };
+
+        let width = if self.body_width() > 2 { self.body_width() - 2 } else { 0 };
+        let title = " 連結 (links) ";
+        let footer = " 1-9 開啟 (open)  Esc 關閉 (close) ";
+        let border = |text: &str, corner: &str| {
+            let fill = if width > jks_len(text) { width - jks_len(text) } else { 0 };
+            format!("{}{}{}", corner, text, seq_str_gen(0, fill, "─", ""))
+        };
+
+        let mut lines = vec![border(title, "┌")];
+        for (i, link) in links.iter().take(9).enumerate() {
+            let text = link.text();
+            let label = if text.is_empty() || text == link.url {
+                format!("{}. {}", i + 1, link.url)
+            } else {
+                format!("{}. {} - {}", i + 1, text, link.url)
+            };
+            lines.push(format!("│ {}", if width > 4 { substring(&label, width - 2) } else { label }));
+        }
+        lines.push(border(footer, "└"));
+
+        for (i, line) in lines.iter().enumerate() {
+            write!(stdout, "{}{}{}{}{}{}",
+                    ::termion::cursor::Goto(1, (i + 3) as u16),
+                    ::termion::clear::CurrentLine,
+                    ::termion::color::Fg(::termion::color::Cyan),
+                    format!(" {}", line),
+                    ::termion::style::Reset,
+                    ::termion::cursor::Hide).expect("fail to write to shell");
+        }
+    }
+
+    fn print_separator_top<W: Write>(&mut self, stdout: &mut W, reply: &ShowReplyItem) {
         if self.can_print() {
-            let (replier_name, time) = make_separator_content(&reply);
-            let s = self.build_separator_top(&replier_name, &time);
+            let (label, replier_name, time) = make_separator_content(&reply, self.display_zone);
+            let name_style = gender_style_codes(reply.gender, self.color_support);
+            let s = self.build_separator_top(&label, &replier_name, &name_style, &time);
             self.print_separator_line(stdout, &s);
         }
     }
 
-    fn print_separator_bottom(&mut self, stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>) {
+    fn print_separator_bottom<W: Write>(&mut self, stdout: &mut W) {
         if self.can_print() {
             let s = self.build_separator_bottom();
             self.print_separator_line(stdout, &s);
         }
     }
 
-    fn print_separator_line(&mut self, stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>, s: &str) {
+    fn print_separator_line<W: Write>(&mut self, stdout: &mut W, s: &str) {
 
         write!(stdout, "{}{}{}{}{}{}",
                 ::termion::cursor::Goto(1, (self.scrolled_y() + 1) as u16),
@@ -69,9 +256,9 @@ impl Show {
                 ::termion::cursor::Hide).expect("fail to write to shell");
     }
 
-    fn print_header(&mut self, stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>, text: &str) {
+    fn print_header<W: Write>(&mut self, stdout: &mut W, text: &str) {
         let title_len = jks_len(text);
-        let w = ::termion::terminal_size().expect("fail to get terminal size").0 as usize;
+        let w = self.terminal_size().0 as usize;
         let padding = ((if w >= title_len {
             w - title_len
         } else {
@@ -96,13 +283,19 @@ impl Show {
                 ::termion::cursor::Hide).expect("fail to write to shell");
     }
 
-    pub fn print_body(&mut self, stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>, item: &ShowItem) {
-        let width = self.body_width();
-        let rows = self.body_height();
+    pub fn print_body<W: Write>(&mut self, stdout: &mut W, item: &ShowItem) {
+        self.reply_offsets.clear();
 
-        for (i, reply) in item.replies.iter().take(rows).enumerate() {
+        // every reply is laid out, only those within the body are written
+        for reply in item.replies.iter() {
 
-            self.print_reply(stdout, &reply.body, 0);
+            self.reply_offsets.push(self.y);
+            self.link_number = 0;
+
+            match reply.diagnostic {
+                Some(_) => self.print_placeholder(stdout, &reply.body),
+                None => self.print_reply(stdout, &reply.body, 0),
+            }
 
             self.print_separator_top(stdout, &reply);
             self.y += 1;
@@ -116,7 +309,7 @@ impl Show {
         self.is_scroll_to_end = self.scrolled_y() < self.body_height();
     }
 
-    fn print_reply_line(&mut self, stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>, s: String) {
+    fn print_reply_line<W: Write>(&mut self, stdout: &mut W, s: String) {
 
        write!(stdout, "{}{}{}{}{}",
                ::termion::cursor::Goto(1, (self.scrolled_y() + 1) as u16),
@@ -126,7 +319,41 @@ impl Show {
                ::termion::cursor::Hide).expect("fail to write to shell");
     }
 
-    fn print_reply(&mut self, stdout: &mut ::termion::raw::RawTerminal<std::io::StdoutLock>, vec: &Vec<NodeType>, depth: usize) {
+    // a reply the builder could not parse, boxed and shown as raw text
+    fn print_placeholder<W: Write>(&mut self, stdout: &mut W, vec: &Vec<NodeType>) {
+        let width = if self.body_width() > 2 { self.body_width() - 2 } else { 0 };
+        let title = " 未能解析 (could not parse) ";
+        let top_width = if width > jks_len(title) { width - jks_len(title) } else { 0 };
+
+        let mut lines = vec![format!("┌{}{}", title, seq_str_gen(0, top_width, "─", ""))];
+        for node in vec {
+            match *node {
+                NodeType::Text(ref n) => {
+                    for text in n.data.lines() {
+                        // long lines are cut, wrapping would throw off the line count
+                        let text = if width > 4 { substring(text, width - 2) } else { text.to_string() };
+                        lines.push(format!("│ {}", text));
+                    }
+                }
+                _ => {}
+            }
+        }
+        lines.push(format!("└{}", seq_str_gen(0, width, "─", "")));
+
+        for line in lines {
+            if self.can_print() {
+                write!(stdout, "{}{}{}{}{}",
+                        ::termion::cursor::Goto(1, (self.scrolled_y() + 1) as u16),
+                        ::termion::color::Fg(::termion::color::Yellow),
+                        format!(" {}", line),
+                        ::termion::style::Reset,
+                        ::termion::cursor::Hide).expect("fail to write to shell");
+            }
+            self.y += 1;
+        }
+    }
+
+    fn print_reply<W: Write>(&mut self, stdout: &mut W, vec: &Vec<NodeType>, depth: usize) {
 
         let icon_width = 2;
         let img_height = 10;
@@ -134,10 +361,10 @@ impl Show {
         let mut line = String::new();
         let mut is_first = true;
 
-        let vec_clean = clean_reply_body(vec);
+        let vec_clean = clean_reply_body(&flatten_spans(vec, &Default::default()));
         let mut img_offset = 0;
         let mut text_y_offset = 0;
-        let w = ::termion::terminal_size().expect("fail to get terminal size").0 as usize;
+        let w = self.terminal_size().0 as usize;
 
         for (j, node) in vec_clean.iter().enumerate() {
             match node.clone() {
@@ -149,35 +376,73 @@ impl Show {
                         line = format!("{}{}", line, text);
                     }
                 }
+                NodeType::Span(n) => {
+                    let text = n.data.iter().filter_map(|x| match *x {
+                        NodeType::Text(ref t) => Some(t.data.clone()),
+                        _ => None,
+                    }).collect::<Vec<_>>().join("");
+
+                    if text != "" {
+                        let len = jks_len(&text);
+                        text_y_offset = (if w > 0 { len / w } else { 0 }) + 1;
+                        line = format!("{}{}{}{}{}",
+                                       line,
+                                       span_style_codes(&n.style, self.color_support),
+                                       text,
+                                       ::termion::style::Reset,
+                                       ::termion::color::Fg(::termion::color::White));
+                    }
+                }
+                NodeType::Link(n) => {
+                    self.link_number += 1;
+
+                    let text = n.text();
+                    let visible_text = if self.hyperlinks && !text.is_empty() {
+                        text.clone()
+                    } else {
+                        format!("{}[{}]", text, self.link_number)
+                    };
+
+                    let len = jks_len(&visible_text);
+                    text_y_offset = (if w > 0 { len / w } else { 0 }) + 1;
+
+                    let label = if self.hyperlinks { hyperlink(&n.url, &visible_text) } else { visible_text };
+                    let style = SpanStyle { underline: true, ..Default::default() };
+                    line = format!("{}{}{}{}{}",
+                                   line,
+                                   span_style_codes(&style, self.color_support),
+                                   label,
+                                   ::termion::style::Reset,
+                                   ::termion::color::Fg(::termion::color::White));
+                }
                 NodeType::Image(n) => {
                     if n.data != "" {
                         if self.can_print() {
-                            if n.alt != "" {
-                                match self.get_icon_reference(&n.alt) {
-                                    // ICON
-                                    Some(icon_reference) => line = format!("{}{}", line, imgcat_from_path(&icon_reference, icon_width)),
-                                    // URL IMAGE
-                                    None => {
-                                        if self.can_still_print(img_offset + text_y_offset + img_height) {
-                                            match imgcat_from_url(&n.data, img_height) {
-                                                Ok(img) => {
-                                                    img_offset += img_height;
-                                                    line = format!("{}{}", line, img);
-                                                }
-                                                Err(e) => {
-                                                    img_offset += 1;
-                                                    line = format!("{}\n[x]", line);
-                                                }
+                            match self.icons.find(&n.data, &n.alt).cloned() {
+                                // ICON
+                                Some(icon) => line = format!("{}{}", line, self.icon_text(&icon, icon_width)),
+                                // URL IMAGE
+                                None if n.alt != "" => {
+                                    if self.can_still_print(img_offset + text_y_offset + img_height) {
+                                        match imgcat_from_url(&self.cache, &n.data, img_height) {
+                                            Ok(img) => {
+                                                img_offset += img_height;
+                                                line = format!("{}{}", line, img);
+                                            }
+                                            Err(e) => {
+                                                img_offset += 1;
+                                                line = format!("{}\n[x]", line);
                                             }
-
-                                        } else {
-                                            img_offset += 1;
-                                            line = format!("{}\n[-]", line);
                                         }
+
+                                    } else {
+                                        img_offset += 1;
+                                        line = format!("{}\n[-]", line);
                                     }
                                 }
-                            } else {
-                                line = format!("{}{}", line, format!("[{}]", n.data));
+                                None => {
+                                    line = format!("{}{}", line, format!("[{}]", n.data));
+                                }
                             }
                         }
                     }
@@ -185,13 +450,75 @@ impl Show {
                 NodeType::BlockQuote(n) => {
                     if self.can_still_print(img_offset + text_y_offset) {
                         self.print_reply(stdout, &n.data, depth + 1);
+                    } else {
+                        // keep the numbers of the links after the quote
+                        self.link_number += links(&n.data).len();
+                    }
+                    is_first = false;
+                }
+                NodeType::List(n) => {
+                    self.flush_line(stdout, &padding, &mut line, &mut text_y_offset, &mut img_offset);
+
+                    for (k, item) in n.items.iter().enumerate() {
+                        let marker = if n.ordered { format!("{}. ", k + 1) } else { String::from("• ") };
+                        self.indents.push((marker, true));
+                        self.print_reply(stdout, item, depth);
+                        self.indents.pop();
                     }
                     is_first = false;
                 }
+                NodeType::Table(n) => {
+                    self.flush_line(stdout, &padding, &mut line, &mut text_y_offset, &mut img_offset);
+
+                    let max_width = self.line_width(&padding);
+                    for table_line in make_table(&n, max_width) {
+                        let indent = self.take_indent();
+                        if self.can_print() {
+                            self.print_reply_line(stdout, format!(" {}{}{}", padding, indent, table_line));
+                        }
+                        self.y += 1;
+                    }
+
+                    // cells are shown as plain text, their links are only in the picker
+                    self.link_number += n.links().len();
+                    is_first = false;
+                }
+                NodeType::Pre(n) => {
+                    self.flush_line(stdout, &padding, &mut line, &mut text_y_offset, &mut img_offset);
+
+                    // cut rather than wrapped, so ascii art keeps its shape
+                    let max_width = self.line_width(&padding);
+                    for text in n.data.lines() {
+                        let text = text.replace('\t', "    ");
+                        let text = if max_width > 2 && jks_len(&text) > max_width { substring(&text, max_width + 1) } else { text };
+                        let indent = self.take_indent();
+                        if self.can_print() {
+                            self.print_reply_line(stdout, format!(" {}{}{}", padding, indent, text));
+                        }
+                        self.y += 1;
+                    }
+                    is_first = false;
+                }
+                NodeType::Hr(_) => {
+                    self.flush_line(stdout, &padding, &mut line, &mut text_y_offset, &mut img_offset);
+
+                    let rule = seq_str_gen(0, self.line_width(&padding), "─", "");
+                    let indent = self.take_indent();
+                    if self.can_print() {
+                        self.print_reply_line(stdout, format!(" {}{}{}{}",
+                                                              padding,
+                                                              indent,
+                                                              ::termion::color::Fg(::termion::color::LightBlack),
+                                                              rule));
+                    }
+                    self.y += 1;
+                    is_first = false;
+                }
                 NodeType::Br(n) => {
                     if !line.is_empty() {
+                        let indent = self.take_indent();
                         if self.can_print() {
-                            self.print_reply_line(stdout, format!(" {}{}", padding, line));
+                            self.print_reply_line(stdout, format!(" {}{}{}", padding, indent, line));
                         }
                         line = String::new();
                         is_first = false;
@@ -218,8 +545,9 @@ impl Show {
 
         if !line.is_empty() {
 
+            let indent = self.take_indent();
             if self.can_print() {
-                self.print_reply_line(stdout, format!(" {}{}", padding, line));
+                self.print_reply_line(stdout, format!(" {}{}{}", padding, indent, line));
 
                 if text_y_offset > 0 {
                     self.y += text_y_offset;
@@ -237,16 +565,67 @@ impl Show {
         }
     }
 
-    fn get_icon_reference(&mut self, alt: &str) -> Option<String> {
-        match self.icon_collection.iter().find(|icon_item| icon_item.alt.contains(&alt) ) {
-            Some(item) => Some(format!("data/icon/{}", &item.src)),
-            None => None
+    // ends the line in progress before a block element
+    fn flush_line<W: Write>(&mut self,
+                  stdout: &mut W,
+                  padding: &str,
+                  line: &mut String,
+                  text_y_offset: &mut usize,
+                  img_offset: &mut usize) {
+        if line.is_empty() {
+            return;
+        }
+
+        let indent = self.take_indent();
+        if self.can_print() {
+            self.print_reply_line(stdout, format!(" {}{}{}", padding, indent, line));
+        }
+
+        self.y += if *text_y_offset + *img_offset > 0 { *text_y_offset + *img_offset } else { 1 };
+
+        line.clear();
+        *text_y_offset = 0;
+        *img_offset = 0;
+    }
+
+    // each list item shows its marker on its first line, the lines after it are indented as far
+    fn take_indent(&mut self) -> String {
+        let mut indent = String::new();
+        for entry in self.indents.iter_mut() {
+            if entry.1 {
+                indent.push_str(&entry.0);
+                entry.1 = false;
+            } else {
+                indent.push_str(&seq_str_gen(0, jks_len(&entry.0), " ", ""));
+            }
+        }
+        indent
+    }
+
+    // columns left for a block element after the quote padding and list indents
+    fn line_width(&self, padding: &str) -> usize {
+        let w = self.terminal_size().0 as usize;
+        let used = 2 + jks_len(padding) + self.indents.iter().map(|x| jks_len(&x.0)).sum::<usize>();
+        if w > used { w - used } else { 0 }
+    }
+
+    fn icon_text(&self, icon: &Icon, width: usize) -> String {
+        if !self.inline_images {
+            return icon.fallback_text();
+        }
+
+        match icon.image_bytes() {
+            Ok(bytes) => imgcat_from_bytes(bytes, width),
+            Err(e) => {
+                error!("[icon error] src: {}, reason: {}", icon.src, e);
+                icon.fallback_text()
+            }
         }
     }
 
     fn build_separator_arguments(&mut self) -> (usize, usize, String) {
         let separator_width = self.body_width();
-        let w = ::termion::terminal_size().expect("fail to get terminal size").0 as usize;
+        let w = self.terminal_size().0 as usize;
 
         let separator_padding_width = if w > separator_width {
             w - separator_width
@@ -259,13 +638,15 @@ impl Show {
         (separator_width, separator_padding_width, separator_padding)
     }
 
-    fn build_separator_top(&mut self, replier_name: &str, time: &str) -> String {
+    fn build_separator_top(&mut self, label: &str, replier_name: &str, name_style: &str, time: &str) -> String {
         let (separator_width, separator_padding_width, separator_padding) =
             self.build_separator_arguments();
         make_separator_top(separator_width,
                            &separator_padding,
+                           &label,
                            self.replier_max_width,
                            &replier_name,
+                           &name_style,
                            self.time_max_width,
                            &time)
     }
@@ -311,7 +692,7 @@ impl Show {
 
     pub fn body_height(&self) -> usize {
 
-        let h = ::termion::terminal_size().expect("fail to get terminal size").1;
+        let h = self.terminal_size().1;
 
         if h >= 3 {
             h as usize - 3
@@ -322,7 +703,7 @@ impl Show {
 
     pub fn body_width(&self) -> usize {
 
-        let w = ::termion::terminal_size().expect("fail to get terminal size").0;
+        let w = self.terminal_size().0;
 
         if w >= 2 {
             w as usize - 2
@@ -349,43 +730,143 @@ impl Show {
 
 
 
-fn make_separator_content(reply: &ShowReplyItem) -> (String, String) {
-    let now = Local::now();
-
+fn make_separator_content(reply: &ShowReplyItem, zone: DisplayZone) -> (String, String, String) {
     let replier_name = reply.username.clone();
 
-    let published_at = reply.published_at.clone();
+    // floor and badges, left of the box
+    let mut marks = Vec::new();
+    match reply.floor {
+        Some(floor) => marks.push(format!("#{}", floor)),
+        None => {}
+    }
+    if reply.is_op {
+        marks.push(String::from("[OP]"));
+    }
+    if reply.is_moderator {
+        marks.push(String::from("[MOD]"));
+    }
+    let label = marks.join(" ");
+
+    // a time that could not be parsed is shown as unknown rather than as now
+    let time = match reply.published_at.seconds {
+        Some(seconds) => published_at_format(seconds, zone),
+        None => String::from("?"),
+    };
+    (label, replier_name, time)
+}
+
+// the colours hkgolden gives names, nothing for unknown
+fn gender_style_codes(gender: Gender, support: ColorSupport) -> String {
+    let color = match gender {
+        Gender::Male => "#6699ff",
+        Gender::Female => "#ff6699",
+        Gender::Unknown => return String::new(),
+    };
+    let style = SpanStyle { color: Some(String::from(color)), ..Default::default() };
+    span_style_codes(&style, support)
+}
+
+// spans become styled runs of text, images, line breaks and quotes inside them keep their place unstyled
+fn flatten_spans(vec: &Vec<NodeType>, style: &SpanStyle) -> Vec<NodeType> {
+    let mut result = Vec::new();
+    for node in vec {
+        match *node {
+            NodeType::Span(ref n) => result.append(&mut flatten_spans(&n.data, &style.merge(&n.style))),
+            NodeType::Link(ref n) => {
+                // images, breaks and quotes inside a link follow its text
+                let (texts, mut others): (Vec<NodeType>, Vec<NodeType>) = flatten_spans(&n.data, style).into_iter().partition(|x| match *x {
+                    NodeType::Text(_) | NodeType::Span(_) => true,
+                    _ => false,
+                });
+                result.push(NodeType::Link(LinkNode { url: n.url.clone(), data: texts }));
+                result.append(&mut others);
+            }
+            NodeType::Text(ref n) if !style.is_plain() => {
+                result.push(NodeType::Span(SpanNode { style: style.clone(), data: vec![NodeType::Text(n.clone())] }));
+            }
+            _ => result.push(node.clone()),
+        }
+    }
+    result
+}
+
+// a box-drawn grid, the widest column is narrowed until the table fits
+fn make_table(table: &TableNode, max_width: usize) -> Vec<String> {
+    let rows = table.rows.iter().map(|row| {
+        row.iter().map(|cell| cell_text(&cell.data).split_whitespace().collect::<Vec<_>>().join(" ")).collect::<Vec<_>>()
+    }).collect::<Vec<_>>();
+
+    let columns = rows.iter().map(|x| x.len()).max().unwrap_or(0);
+    if columns == 0 {
+        return Vec::new();
+    }
+
+    let mut widths = (0..columns).map(|j| {
+        rows.iter().map(|row| row.get(j).map_or(0, |x| jks_len(x))).max().unwrap_or(0).max(1)
+    }).collect::<Vec<_>>();
+
+    // a space on each side of every cell and a border between them
+    let borders_width = columns * 3 + 1;
+    while widths.iter().sum::<usize>() + borders_width > max_width {
+        let (j, widest) = widths.iter().cloned().enumerate().max_by_key(|&(_, w)| w).unwrap_or((0, 0));
+        if widest <= 3 {
+            break;
+        }
+        widths[j] = widest - 1;
+    }
 
-    let published_at_dt = match Local.datetime_from_str(&published_at, "%d/%m/%Y %H:%M") {
-        Ok(v) => v,
-        Err(e) => now,
+    let rule = |left: &str, middle: &str, right: &str| {
+        format!("{}{}{}",
+                left,
+                widths.iter().map(|w| seq_str_gen(0, w + 2, "─", "")).collect::<Vec<_>>().join(middle),
+                right)
     };
-    let time = published_at_format(&(now - published_at_dt));
-    (replier_name, time)
+
+    let mut lines = vec![rule("┌", "┬", "┐")];
+    for (i, row) in rows.iter().enumerate() {
+        let cells = (0..columns).map(|j| format!(" {} ", fit_width(row.get(j).map_or("", |x| x.as_str()), widths[j])))
+                                .collect::<Vec<_>>();
+        lines.push(format!("│{}│", cells.join("│")));
+
+        let is_header = !table.rows[i].is_empty() && table.rows[i].iter().all(|x| x.header);
+        if is_header && i + 1 < rows.len() {
+            lines.push(rule("├", "┼", "┤"));
+        }
+    }
+    lines.push(rule("└", "┴", "┘"));
+
+    lines
+}
+
+// cells are drawn as text, an image leaves a placeholder instead of vanishing
+fn cell_text(nodes: &Vec<NodeType>) -> String {
+    nodes.iter().map(|node| match *node {
+        NodeType::Image(_) => String::from(" [img] "),
+        NodeType::Span(ref n) => cell_text(&n.data),
+        NodeType::Link(ref n) => cell_text(&n.data),
+        NodeType::Text(ref n) => n.data.clone(),
+        _ => String::new(),
+    }).collect::<Vec<_>>().join("")
+}
+
+// pads or cuts the text to the number of columns, wide characters count as two
+fn fit_width(s: &str, width: usize) -> String {
+    let text = if jks_len(s) > width { substring(s, width + 1) } else { s.to_string() };
+    let len = jks_len(&text);
+    format!("{}{}", text, seq_str_gen(0, if width > len { width - len } else { 0 }, " ", ""))
 }
 
 fn clean_reply_body(vec: &Vec<NodeType>) -> Vec<NodeType> {
     // clean up lines (end)
     let vec2 = {
-        let vec_length = vec.len();
-        let vec_check_cleanup = vec.clone();
-
-        // check if last 4 elements match the EMPTY PATTERN
-        let is_last4_empty = vec_check_cleanup.iter()
-                                              .rev()
-                                              .take(4)
-                                              .enumerate()
-                                              .all(|(j, node)| match node.clone() {
-                                                  NodeType::Br(n) => j == 1 || j == 2 || j == 3,
-                                                  NodeType::Text(n) => j == 0 && n.data.is_empty(),
-                                                  _ => false,
-                                              });
-
-        let vec_short_length = if vec_length > 4 && is_last4_empty {
-            vec_length - 4
-        } else {
-            vec_length
-        };
+        // drop trailing line breaks and blank text
+        let vec_short_length = vec.iter()
+                                  .rposition(|node| match *node {
+                                      NodeType::Br(_) => false,
+                                      NodeType::Text(ref n) => !n.data.trim().is_empty(),
+                                      _ => true,
+                                  })
+                                  .map_or(0, |x| x + 1);
 
         vec.iter().take(vec_short_length)
     };
@@ -476,8 +957,10 @@ fn make_separator_time(separator_width: usize,
 
 fn make_separator_top(separator_width: usize,
                       separator_padding: &str,
+                      label: &str,
                       replier_max_width: usize,
                       replier_name: &str,
+                      name_style: &str,
                       time_max_width: usize,
                       time: &str)
                       -> String {
@@ -503,10 +986,33 @@ fn make_separator_top(separator_width: usize,
         0
     };
 
+    let label = if label.is_empty() { String::new() } else { format!("{} ", label) };
+    let label_width = jks_len(&label);
+    let (label, separator_top_middle_width) = if separator_top_middle_width >= label_width {
+        (label, separator_top_middle_width - label_width)
+    } else {
+        (String::new(), separator_top_middle_width)
+    };
+
+    // widths are measured on the plain name, the colour only goes in afterwards
+    let separator_replier = if name_style.is_empty() || replier_name.is_empty() {
+        separator_replier
+    } else {
+        separator_replier.replacen(replier_name,
+                                   &format!("{}{}{}{}{}",
+                                            name_style,
+                                            replier_name,
+                                            ::termion::style::Reset,
+                                            ::termion::color::Fg(::termion::color::Green),
+                                            ::termion::style::Bold),
+                                   1)
+    };
+
     let separator_top_middle = seq_str_gen(0, separator_top_middle_width, " ", "");
-    let separator_top = format!("{}{}{}{}{}",
+    let separator_top = format!("{}{}{}{}{}{}",
                                 separator_padding,
                                 separator_top_middle,
+                                label,
                                 separator_replier,
                                 separator_time,
                                 separator_padding);
@@ -531,14 +1037,21 @@ fn make_separator_bottom(separator_width: usize, separator_padding: &str) -> Str
 }
 
 
-fn published_at_format(duration: &Duration) -> String {
+// the age of recent replies, the date in the display zone of those older than a week
+fn published_at_format(seconds: i64, zone: DisplayZone) -> String {
+    let now = UTC::now().timestamp();
+    let duration = Duration::seconds(now - seconds);
     let weeks = duration.num_weeks();
     let days = duration.num_days();
     let hours = duration.num_hours();
     let minutes = duration.num_minutes();
 
     if weeks > 0 {
-        format!("{}w", weeks)
+        if zone.format(seconds, "%Y") == zone.format(now, "%Y") {
+            zone.format(seconds, "%d/%m")
+        } else {
+            zone.format(seconds, "%Y")
+        }
     } else if days > 0 {
         format!("{}d", days)
     } else if hours > 0 {
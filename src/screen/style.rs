@@ -0,0 +1,117 @@
+use std::env;
+
+use reply_model::SpanStyle;
+use utility::color::*;
+
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum ColorSupport {
+    None,
+    Basic,
+    TrueColor,
+}
+
+// NO_COLOR and dumb terminals get plain text, 24-bit colour only when COLORTERM says so
+pub fn color_support() -> ColorSupport {
+    if env::var("NO_COLOR").is_ok() {
+        return ColorSupport::None;
+    }
+
+    match env::var("TERM") {
+        Ok(ref term) if term == "dumb" => return ColorSupport::None,
+        _ => {}
+    }
+
+    match env::var("COLORTERM") {
+        Ok(ref colorterm) if colorterm == "truecolor" || colorterm == "24bit" => ColorSupport::TrueColor,
+        _ => ColorSupport::Basic,
+    }
+}
+
+// escape codes starting a styled span, the caller resets the style after the text
+pub fn span_style_codes(style: &SpanStyle, support: ColorSupport) -> String {
+    if support == ColorSupport::None {
+        return String::new();
+    }
+
+    let mut codes = String::new();
+
+    // a terminal cannot change the font size, big text is bold and tiny text is faint
+    let size = style.size.unwrap_or(3);
+
+    if style.bold || size >= 5 {
+        codes.push_str(&format!("{}", ::termion::style::Bold));
+    }
+    if size <= 1 {
+        codes.push_str(&format!("{}", ::termion::style::Faint));
+    }
+    if style.italic {
+        codes.push_str(&format!("{}", ::termion::style::Italic));
+    }
+    if style.underline {
+        codes.push_str(&format!("{}", ::termion::style::Underline));
+    }
+    if style.strikethrough {
+        codes.push_str(&format!("{}", ::termion::style::CrossedOut));
+    }
+
+    match style.color.as_ref().and_then(|x| parse_css_color(x)) {
+        Some(rgb) => codes.push_str(&color_code(rgb, support)),
+        None => {}
+    }
+
+    codes
+}
+
+fn color_code((r, g, b): (u8, u8, u8), support: ColorSupport) -> String {
+    if support == ColorSupport::TrueColor {
+        return format!("{}", ::termion::color::Fg(::termion::color::Rgb(r, g, b)));
+    }
+
+    match nearest_basic_color((r, g, b)) {
+        // black text would vanish on a dark terminal
+        0 => format!("{}", ::termion::color::Fg(::termion::color::LightBlack)),
+        1 => format!("{}", ::termion::color::Fg(::termion::color::Red)),
+        2 => format!("{}", ::termion::color::Fg(::termion::color::Green)),
+        3 => format!("{}", ::termion::color::Fg(::termion::color::Yellow)),
+        4 => format!("{}", ::termion::color::Fg(::termion::color::Blue)),
+        5 => format!("{}", ::termion::color::Fg(::termion::color::Magenta)),
+        6 => format!("{}", ::termion::color::Fg(::termion::color::Cyan)),
+        _ => format!("{}", ::termion::color::Fg(::termion::color::White)),
+    }
+}
+
+// OSC 8 hyperlinks, HKG_HYPERLINKS=1 or 0 overrides the guess from the terminal
+pub fn hyperlink_support() -> bool {
+    match env::var("HKG_HYPERLINKS") {
+        Ok(ref value) if value == "1" => return true,
+        Ok(ref value) if value == "0" => return false,
+        _ => {}
+    }
+
+    match env::var("TERM_PROGRAM") {
+        Ok(ref program) if program == "iTerm.app" || program == "WezTerm" || program == "vscode" || program == "Hyper" => return true,
+        _ => {}
+    }
+
+    let vte_version = env::var("VTE_VERSION").ok().and_then(|x| x.parse::<u32>().ok()).unwrap_or(0);
+
+    vte_version >= 5000 || env::var("KONSOLE_VERSION").is_ok() || env::var("KITTY_WINDOW_ID").is_ok() || env::var("WT_SESSION").is_ok()
+}
+
+pub fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\", url = url, text = text)
+}
+
+// the iTerm2 inline image protocol, icons fall back to text or emoji elsewhere
+pub fn inline_image_support() -> bool {
+    match env::var("TERM_PROGRAM") {
+        Ok(ref program) if program == "iTerm.app" || program == "WezTerm" => return true,
+        _ => {}
+    }
+
+    match env::var("LC_TERMINAL") {
+        Ok(ref terminal) if terminal == "iTerm2" => true,
+        _ => false,
+    }
+}
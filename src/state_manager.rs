@@ -11,18 +11,20 @@ pub struct StateManager {
     is_web_requesting: bool,
     is_bg_requesting: bool,
     tx_state: Sender<(Status,Status)>,
-    to_print_screen: Arc<AtomicBool>
+    to_print_screen: Arc<AtomicBool>,
+    offline: Arc<AtomicBool>
 }
 
 impl StateManager {
-    pub fn new (tx_state: Sender<(Status,Status)>) -> Self {
+    pub fn new (tx_state: Sender<(Status,Status)>, offline: Arc<AtomicBool>) -> Self {
         StateManager {
             current_state: Status::Startup,
             prev_state: Status::Startup,
             is_web_requesting: false,
             is_bg_requesting: false,
             tx_state: tx_state,
-            to_print_screen: Arc::new(AtomicBool::new(false))
+            to_print_screen: Arc::new(AtomicBool::new(false)),
+            offline: offline
         }
     }
     pub fn is_web_request (&self) -> bool {
@@ -61,4 +63,12 @@ impl StateManager {
     pub fn set_to_print_screen(&mut self, value: bool) {
         (*self.to_print_screen).store(value, Ordering::Relaxed)
     }
+
+    pub fn is_offline(&self) -> bool {
+        (*self.offline).load(Ordering::Relaxed)
+    }
+
+    pub fn set_offline(&mut self, value: bool) {
+        (*self.offline).store(value, Ordering::Relaxed)
+    }
 }
@@ -0,0 +1,13 @@
+// 64-bit FNV-1a, enough to notice a truncated or garbled cache entry
+pub fn fnv1a64(buffer: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in buffer {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+pub fn checksum(buffer: &[u8]) -> String {
+    format!("{:016x}", fnv1a64(buffer))
+}
@@ -0,0 +1,71 @@
+// css colours of the markup as rgb, hkgolden only uses a handful of names
+pub fn parse_css_color(value: &str) -> Option<(u8, u8, u8)> {
+    let value = value.trim().to_lowercase();
+
+    if value.starts_with('#') {
+        let hex = &value[1..];
+        // byte slicing below needs ascii
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+        return match hex.len() {
+            3 => {
+                match (channel(&hex[0..1]), channel(&hex[1..2]), channel(&hex[2..3])) {
+                    (Some(r), Some(g), Some(b)) => Some((r * 17, g * 17, b * 17)),
+                    _ => None,
+                }
+            }
+            6 => {
+                match (channel(&hex[0..2]), channel(&hex[2..4]), channel(&hex[4..6])) {
+                    (Some(r), Some(g), Some(b)) => Some((r, g, b)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+    }
+
+    if value.starts_with("rgb(") && value.ends_with(')') {
+        let channels = value[4..value.len() - 1].split(',')
+                                                .filter_map(|x| x.trim().parse::<u8>().ok())
+                                                .collect::<Vec<_>>();
+        return if channels.len() == 3 { Some((channels[0], channels[1], channels[2])) } else { None };
+    }
+
+    match value.as_str() {
+        "black" => Some((0, 0, 0)),
+        "white" => Some((255, 255, 255)),
+        "red" => Some((255, 0, 0)),
+        "green" => Some((0, 128, 0)),
+        "blue" => Some((0, 0, 255)),
+        "yellow" => Some((255, 255, 0)),
+        "purple" => Some((128, 0, 128)),
+        "violet" => Some((238, 130, 238)),
+        "pink" => Some((255, 192, 203)),
+        "orange" => Some((255, 165, 0)),
+        "brown" => Some((165, 42, 42)),
+        "gray" | "grey" => Some((128, 128, 128)),
+        "navy" => Some((0, 0, 128)),
+        "teal" => Some((0, 128, 128)),
+        "maroon" => Some((128, 0, 0)),
+        "olive" => Some((128, 128, 0)),
+        "lime" => Some((0, 255, 0)),
+        "aqua" | "cyan" => Some((0, 255, 255)),
+        "fuchsia" | "magenta" => Some((255, 0, 255)),
+        "silver" => Some((192, 192, 192)),
+        _ => None,
+    }
+}
+
+// index of the closest of the 8 basic terminal colours, bit 0 red, bit 1 green, bit 2 blue
+pub fn nearest_basic_color((r, g, b): (u8, u8, u8)) -> u8 {
+    let max = *[r, g, b].iter().max().unwrap_or(&0);
+    if max < 64 {
+        return 0;
+    }
+    // channels close to the strongest one count, so pink ends up white and orange ends up red
+    let threshold = max / 2 + max / 4;
+    let bit = |x: u8, shift: u8| if x >= threshold { 1 << shift } else { 0 };
+    bit(r, 0) | bit(g, 1) | bit(b, 2)
+}
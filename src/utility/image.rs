@@ -0,0 +1,38 @@
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum ImageFormat {
+    Gif,
+    Png,
+    Jpeg,
+    WebP,
+}
+
+impl ImageFormat {
+    pub fn content_type(&self) -> &'static str {
+        match *self {
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::WebP => "image/webp",
+        }
+    }
+}
+
+// detect the image format by the magic bytes at the start of the buffer
+pub fn image_format(buffer: &[u8]) -> Option<ImageFormat> {
+    if buffer.starts_with(b"GIF87a") || buffer.starts_with(b"GIF89a") {
+        Some(ImageFormat::Gif)
+    } else if buffer.starts_with(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some(ImageFormat::Png)
+    } else if buffer.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageFormat::Jpeg)
+    } else if buffer.len() >= 12 && buffer.starts_with(b"RIFF") && &buffer[8..12] == b"WEBP" {
+        Some(ImageFormat::WebP)
+    } else {
+        None
+    }
+}
+
+pub fn is_image(buffer: &[u8]) -> bool {
+    image_format(buffer).is_some()
+}
@@ -1,4 +1,8 @@
 pub mod string;
+pub mod image;
+pub mod checksum;
+pub mod color;
+pub mod time;
 
 // open.rs
 use std::error::Error;
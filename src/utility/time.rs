@@ -0,0 +1,64 @@
+use chrono::*;
+
+// the forum prints its times in Hong Kong time without a zone
+pub const HKT_OFFSET_SECS: i32 = 8 * 60 * 60;
+
+pub const FORUM_TIME_FORMAT: &'static str = "%d/%m/%Y %H:%M";
+
+pub fn hkt() -> FixedOffset {
+    FixedOffset::east(HKT_OFFSET_SECS)
+}
+
+pub fn parse_hkt(text: &str, format: &str) -> Option<DateTime<FixedOffset>> {
+    hkt().datetime_from_str(text.trim(), format).ok()
+}
+
+// the zone times are shown in, the machine's own or a fixed offset
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+pub enum DisplayZone {
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl DisplayZone {
+    // "local", "UTC", "HKT" or an offset like "+08:00", "-0530" or "+8"
+    pub fn parse(s: &str) -> Result<DisplayZone, String> {
+        let s = s.trim();
+        match s.to_lowercase().as_str() {
+            "local" => return Ok(DisplayZone::Local),
+            "utc" | "gmt" | "z" => return Ok(DisplayZone::Fixed(FixedOffset::east(0))),
+            "hkt" => return Ok(DisplayZone::Fixed(hkt())),
+            _ => {}
+        }
+
+        let sign = match s.chars().next() {
+            Some('+') => 1,
+            Some('-') => -1,
+            _ => return Err(format!("invalid timezone: {}", s)),
+        };
+
+        let digits = s[1..].replace(":", "");
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("invalid timezone: {}", s));
+        }
+        let (hours, minutes) = match digits.len() {
+            1 | 2 => (digits.parse::<i32>().ok(), Some(0)),
+            4 => (digits[..2].parse::<i32>().ok(), digits[2..].parse::<i32>().ok()),
+            _ => (None, None),
+        };
+
+        match (hours, minutes) {
+            (Some(h), Some(m)) if h < 24 && m < 60 => Ok(DisplayZone::Fixed(FixedOffset::east(sign * (h * 3600 + m * 60)))),
+            _ => Err(format!("invalid timezone: {}", s)),
+        }
+    }
+
+    pub fn format(&self, seconds: i64, format: &str) -> String {
+        match *self {
+            DisplayZone::Local => Local.timestamp(seconds, 0).format(format).to_string(),
+            DisplayZone::Fixed(offset) => offset.timestamp(seconds, 0).format(format).to_string(),
+        }
+    }
+}
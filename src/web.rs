@@ -2,13 +2,16 @@ use std::thread;
 use std::sync::mpsc::{Receiver, Sender};
 use cancellation::CancellationTokenSource;
 
-use caches::file_cache::*;
+use caches::shared_cache::*;
+use backends::Backends;
 use resources::*;
 use resources::common::*;
 use resources::index_resource::*;
+use resources::filter_resource::*;
 use resources::show_resource::*;
 use resources::image_resource::*;
 use resources::web_resource::*;
+use resources::history_resource::*;
 
 use std::sync::{Arc};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -16,7 +19,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 pub struct Requester {}
 
 impl Requester {
-    pub fn new(rx_req: Receiver<ChannelItem>, tx_res: Sender<ChannelItem>, working: Arc<AtomicBool>) -> Self {
+    pub fn new(rx_req: Receiver<ChannelItem>, tx_res: Sender<ChannelItem>, working: Arc<AtomicBool>, offline: Arc<AtomicBool>, cache: SharedCache, backends: Backends, filter_walk: FilterWalk) -> Self {
 
         // web client
         thread::spawn(move || {
@@ -25,9 +28,15 @@ impl Requester {
                 match rx_req.recv() {
                     Ok(item) => {
                         let mut wr = WebResource::new();
-                        let mut fc = Box::new(FileCache::new());
-                        let ct = CancellationTokenSource::new();
-                        ct.cancel_after(::std::time::Duration::new(10, 0));
+                        let mut fc = cache.clone();
+                        let backends = backends.clone();
+                        let is_offline = (*offline).load(Ordering::Relaxed);
+                        let ct = Arc::new(CancellationTokenSource::new());
+                        // a filter walks a whole thread page by page, each page has its own http timeouts
+                        match item.extra {
+                            Some(ChannelItemType::Filter(_)) => start_walk(&filter_walk, ct.clone()),
+                            _ => ct.cancel_after(::std::time::Duration::new(10, 0)),
+                        }
                         let tx_res2 = tx_res.clone();
                         thread::spawn(move || {
                             info!("[requester] #recv");
@@ -36,18 +45,36 @@ impl Requester {
                                 Some(o) => {
                                     info!("request: {:?}", o);
                                     match o {
-                                        ChannelItemType::Index(_) => {
-                                            let mut index_resource = IndexResource::new(&mut wr, &mut fc);
+                                        ChannelItemType::Index(extra) => {
+                                            let backend = backends.get(&extra.forum);
+                                            let channel = backends.channel_or_default(&extra.forum, &extra.channel);
+                                            let mut index_resource = IndexResource::new(&mut wr, &mut fc, &*backend, &channel, is_offline);
                                             tx_res2.send(index_resource.fetch(&item)).expect("[web client] fail to send index request");
                                         }
-                                        ChannelItemType::Show(_) => {
-                                            let mut show_resource = ShowResource::new(&mut wr, &mut fc);
+                                        ChannelItemType::Show(extra) => {
+                                            let backend = backends.get(&extra.forum);
+                                            let channel = backends.channel_or_default(&extra.forum, &extra.channel);
+                                            let mut show_resource = ShowResource::new(&mut wr, &mut fc, &*backend, &channel, is_offline);
                                             tx_res2.send(show_resource.fetch(&item)).expect("[web client] fail to send show request");
                                         }
                                         ChannelItemType::Image(_) => {
-                                            let mut image_resource = ImageResource::new(&mut fc);
+                                            let mut image_resource = ImageResource::new(&mut fc, is_offline);
                                             tx_res2.send(image_resource.fetch(&item)).expect("[web client] fail to send image request");
                                         }
+                                        ChannelItemType::History(extra) => {
+                                            let backend = backends.get(&extra.forum);
+                                            let channel = backends.channel_or_default(&extra.forum, &extra.channel);
+                                            let mut history_resource = HistoryResource::new(&mut fc, &*backend, &channel);
+                                            tx_res2.send(history_resource.fetch(&item)).expect("[web client] fail to send history request");
+                                        }
+                                        ChannelItemType::Filter(extra) => {
+                                            let backend = backends.get(&extra.forum);
+                                            let channel = backends.channel_or_default(&extra.forum, &extra.channel);
+                                            let mut filter_resource = FilterResource::new(&mut wr, &mut fc, &*backend, &channel, is_offline)
+                                                                          .with_partial_results(tx_res2.clone())
+                                                                          .with_cancellation(ct.clone());
+                                            tx_res2.send(filter_resource.fetch(&item)).expect("[web client] fail to send filter request");
+                                        }
                                     }
                                 }
                                 None => { tx_res2.send(Default::default()); }
@@ -0,0 +1,129 @@
+// not every test uses every helper
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::Mutex;
+use std::thread;
+
+use hkg::caches::common::*;
+use hkg::resources::web_resource::{WebResource, WebResponse};
+
+// a stand-in for a forum api, answering with the fixture of the first route the path starts with
+pub fn serve_fixtures(routes: Vec<(&'static str, &'static str)>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("fail to bind stand-in server");
+    let base_url = format!("http://{}", listener.local_addr().expect("fail to get local address"));
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+
+            let mut request_line = String::new();
+            let path = {
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request_line).expect("fail to read request");
+                // drain the headers
+                let mut line = String::new();
+                while reader.read_line(&mut line).map(|n| n > 2).unwrap_or(false) {
+                    line.clear();
+                }
+                request_line.split_whitespace().nth(1).unwrap_or("").to_string()
+            };
+
+            let fixture = routes.iter().find(|&&(prefix, _)| path.starts_with(prefix)).map(|&(_, name)| name);
+
+            let response = match fixture {
+                Some(name) => {
+                    let body = read_fixture(name);
+                    format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(),
+                            body)
+                }
+                None => String::from("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+            };
+            stream.write_all(response.as_bytes()).expect("fail to write response");
+        }
+    });
+
+    base_url
+}
+
+pub fn read_fixture(name: &str) -> String {
+    let mut s = String::new();
+    File::open(format!("tests/fixtures/{}", name)).and_then(|mut f| f.read_to_string(&mut s))
+                                                   .expect("fail to read fixture");
+    s
+}
+
+pub fn fetch(url: &str) -> String {
+    match WebResource::new().fetch_conditional(url, None, None).expect("fail to fetch from stand-in server") {
+        WebResponse::Modified(page) => page.body,
+        WebResponse::NotModified => panic!("unexpected not modified"),
+    }
+}
+
+// a cache kept in memory, to start the resources with pages already downloaded
+#[derive(Default)]
+pub struct TestCache {
+    entries: Mutex<BTreeMap<String, CacheEntry>>
+}
+
+impl TestCache {
+    pub fn with_fixture(self, key: &str, name: &str) -> Self {
+        let meta = CacheMeta { url: key.to_string(), ..Default::default() };
+        self.write(key, read_fixture(name).into_bytes(), meta).expect("fail to add fixture");
+        self
+    }
+}
+
+impl Cache for TestCache {
+    fn read(&self, key: &str) -> Result<CacheEntry, String> {
+        let entries = self.entries.lock().expect("fail to lock test cache");
+        entries.get(key).cloned().ok_or(format!("{} not found", key))
+    }
+
+    fn read_meta(&self, key: &str) -> Result<CacheMeta, String> {
+        self.read(key).map(|x| x.meta)
+    }
+
+    fn write(&self, key: &str, body: Vec<u8>, meta: CacheMeta) -> Result<(), String> {
+        let mut entries = self.entries.lock().expect("fail to lock test cache");
+        entries.insert(key.to_string(), CacheEntry { body: body, meta: meta });
+        Ok(())
+    }
+
+    fn write_meta(&self, key: &str, meta: CacheMeta) -> Result<(), String> {
+        let mut entries = self.entries.lock().expect("fail to lock test cache");
+        match entries.get_mut(key) {
+            Some(entry) => {
+                entry.meta = meta;
+                Ok(())
+            }
+            None => Err(format!("{} not found", key)),
+        }
+    }
+
+    fn touch(&self, _key: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn exists(&self, key: &str) -> bool {
+        self.entries.lock().expect("fail to lock test cache").contains_key(key)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        self.entries.lock().expect("fail to lock test cache").remove(key);
+        Ok(())
+    }
+
+    fn iter<'a>(&'a self, prefix: &str) -> Box<dyn Iterator<Item = String> + 'a> {
+        let entries = self.entries.lock().expect("fail to lock test cache");
+        let keys = entries.keys().filter(|x| x.starts_with(prefix)).cloned().collect::<Vec<_>>();
+        Box::new(keys.into_iter())
+    }
+}
@@ -0,0 +1,149 @@
+extern crate hkg;
+extern crate cancellation;
+
+mod common;
+
+use std::sync::Arc;
+use std::sync::mpsc::channel;
+
+use hkg::backends::json::JsonBackend;
+use hkg::caches::keys::show_key;
+use hkg::resources::*;
+use hkg::resources::common::Resource;
+use hkg::resources::filter_resource::FilterResource;
+use hkg::resources::web_resource::WebResource;
+
+use cancellation::CancellationTokenSource;
+
+use common::TestCache;
+
+const POSTID: &'static str = "6600000";
+
+fn request(userid: &str, max_page: usize) -> ChannelItem {
+    ChannelItem {
+        extra: Some(ChannelItemType::Filter(ChannelFilterItem {
+            postid: String::from(POSTID),
+            userid: userid.to_string(),
+            max_page: max_page,
+            ..Default::default()
+        })),
+        result: Default::default(),
+        unavailable: false,
+    }
+}
+
+fn filter_item(item: ChannelItem) -> ChannelFilterItem {
+    match item.extra {
+        Some(ChannelItemType::Filter(extra)) => extra,
+        _ => panic!("not a filter item"),
+    }
+}
+
+fn two_pages() -> TestCache {
+    TestCache::default().with_fixture(&show_key(POSTID, 1, "json"), "hkgolden_thread_page1.json")
+                        .with_fixture(&show_key(POSTID, 2, "json"), "hkgolden_thread_page2.json")
+}
+
+#[test]
+fn replies_of_the_author_are_gathered_from_every_page() {
+    let backend = JsonBackend::new("http://127.0.0.1:1");
+    let mut wr = WebResource::new();
+    let mut cache = two_pages();
+
+    // offline, so only the cache is read
+    let response = FilterResource::new(&mut wr, &mut cache, &backend, "BW", true).fetch(&request("2001", 1));
+    assert!(response.result.is_empty());
+    let extra = filter_item(response);
+
+    assert!(extra.done);
+    assert_eq!(extra.max_page, 2);
+    assert!(extra.missing_pages.is_empty());
+
+    let item = extra.show_item.expect("no replies gathered");
+    assert_eq!(item.title, "連載小說");
+    assert_eq!((item.page, item.max_page), (1, 2));
+
+    let ids = item.replies.iter().map(|x| x.reply_id.clone().unwrap_or_default()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["1", "3", "5"]);
+    assert!(item.replies.iter().all(|x| x.is_op));
+}
+
+#[test]
+fn any_user_can_be_chosen() {
+    let backend = JsonBackend::new("http://127.0.0.1:1");
+    let mut wr = WebResource::new();
+    let mut cache = two_pages();
+
+    let extra = filter_item(FilterResource::new(&mut wr, &mut cache, &backend, "BW", true).fetch(&request("2003", 1)));
+
+    let item = extra.show_item.expect("no replies gathered");
+    let ids = item.replies.iter().map(|x| x.reply_id.clone().unwrap_or_default()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["4"]);
+    assert!(!item.replies[0].is_op);
+}
+
+#[test]
+fn pages_not_available_are_reported_missing() {
+    let backend = JsonBackend::new("http://127.0.0.1:1");
+    let mut wr = WebResource::new();
+    let mut cache = TestCache::default().with_fixture(&show_key(POSTID, 2, "json"), "hkgolden_thread_page2.json");
+
+    // the reader knows of two pages, page 1 was never downloaded
+    let response = FilterResource::new(&mut wr, &mut cache, &backend, "BW", true).fetch(&request("2001", 2));
+    assert!(!response.unavailable);
+    let extra = filter_item(response);
+
+    assert_eq!(extra.missing_pages, vec![1]);
+    let item = extra.show_item.expect("no replies gathered");
+    let ids = item.replies.iter().map(|x| x.reply_id.clone().unwrap_or_default()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["5"]);
+}
+
+#[test]
+fn nothing_available_offline_is_unavailable() {
+    let backend = JsonBackend::new("http://127.0.0.1:1");
+    let mut wr = WebResource::new();
+    let mut cache = TestCache::default();
+
+    let response = FilterResource::new(&mut wr, &mut cache, &backend, "BW", true).fetch(&request("2001", 2));
+    assert!(response.unavailable);
+    let extra = filter_item(response);
+
+    assert_eq!(extra.missing_pages, vec![1, 2]);
+    assert!(extra.show_item.is_none());
+}
+
+#[test]
+fn partial_results_are_sent_while_walking() {
+    let backend = JsonBackend::new("http://127.0.0.1:1");
+    let mut wr = WebResource::new();
+    let mut cache = two_pages();
+    let (tx, rx) = channel();
+
+    let extra = filter_item(FilterResource::new(&mut wr, &mut cache, &backend, "BW", true).with_partial_results(tx)
+                                                                                          .fetch(&request("2001", 1)));
+    assert!(extra.done);
+
+    let partials = rx.try_iter().map(filter_item).collect::<Vec<_>>();
+    assert_eq!(partials.len(), 1);
+    assert_eq!((partials[0].page, partials[0].max_page), (1, 2));
+    assert!(!partials[0].done);
+    assert_eq!(partials[0].show_item.as_ref().map(|x| x.replies.len()), Some(2));
+}
+
+#[test]
+fn a_canceled_walk_stops_before_the_next_page() {
+    let backend = JsonBackend::new("http://127.0.0.1:1");
+    let mut wr = WebResource::new();
+    let mut cache = two_pages();
+    let ct = Arc::new(CancellationTokenSource::new());
+    ct.cancel();
+
+    let response = FilterResource::new(&mut wr, &mut cache, &backend, "BW", true).with_cancellation(ct)
+                                                                                 .fetch(&request("2001", 2));
+    assert_eq!(response.result, "canceled");
+    let extra = filter_item(response);
+
+    assert!(extra.done);
+    assert!(extra.show_item.is_none());
+}
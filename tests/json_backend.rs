@@ -0,0 +1,117 @@
+extern crate hkg;
+
+mod common;
+
+use hkg::backends::Backend;
+use hkg::backends::json::JsonBackend;
+use hkg::model::Gender;
+use hkg::reply_model::*;
+
+use common::fetch;
+
+fn serve_fixtures() -> String {
+    common::serve_fixtures(vec![("/topics.aspx", "hkgolden_topics.json"),
+                                ("/view.aspx", "hkgolden_view.json")])
+}
+
+#[test]
+fn topics_are_parsed_from_the_topics_api() {
+    let base_url = serve_fixtures();
+    let backend = JsonBackend::new(&base_url);
+    let url = backend.topics_url("BW", 1);
+
+    let (topics, diagnostics) = backend.parse_topics(fetch(&url).as_bytes(), &url).expect("fail to parse topics");
+
+    assert_eq!(topics.len(), 2);
+    assert_eq!(diagnostics.len(), 1);
+
+    assert_eq!(topics[0].title.text, "今日天氣");
+    assert_eq!(topics[0].title.url_query.forum, "hkgolden");
+    assert_eq!(topics[0].title.url_query.channel, "BW");
+    assert_eq!(topics[0].title.url_query.message, "6500000");
+    assert_eq!(topics[0].title.num_of_pages, 3);
+    assert_eq!(topics[0].author.name, "天文台");
+    assert_eq!(topics[0].reply_count, Some(1024));
+    assert_eq!(topics[0].rating, Some(-3));
+
+    // numbers may come as numbers or strings
+    assert_eq!(topics[1].title.url_query.message, "6500001");
+    assert_eq!(topics[1].title.num_of_pages, 1);
+    assert_eq!(topics[1].reply_count, Some(12));
+    assert_eq!(topics[1].rating, Some(5));
+}
+
+#[test]
+fn dates_are_read_as_milliseconds_since_the_epoch() {
+    let base_url = serve_fixtures();
+    let backend = JsonBackend::new(&base_url);
+    let url = backend.topics_url("BW", 1);
+
+    let (topics, _) = backend.parse_topics(fetch(&url).as_bytes(), &url).expect("fail to parse topics");
+
+    assert_eq!(topics[0].last_replied_at.seconds, Some(1476870000));
+    assert_eq!(topics[1].last_replied_at.seconds, Some(1476873600));
+    assert_eq!(topics[1].last_replied_at.text, "/Date(1476873600000+0800)/");
+}
+
+#[test]
+fn replies_are_parsed_from_the_view_api() {
+    let base_url = serve_fixtures();
+    let backend = JsonBackend::new(&base_url);
+    let url = backend.show_url("BW", "6500000", 2);
+
+    let (item, diagnostics) = backend.parse_show(fetch(&url).as_bytes(), &url).expect("fail to parse thread");
+
+    assert_eq!(item.url_query.forum, "hkgolden");
+    assert_eq!(item.url_query.channel, "BW");
+    assert_eq!(item.url_query.message, "6500000");
+    assert_eq!(item.title, "今日天氣");
+    assert_eq!((item.page, item.max_page), (2, 3));
+    assert_eq!(item.reply_count, Some(1024));
+
+    // the reply without a body stays as a placeholder
+    assert_eq!(item.replies.len(), 4);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(item.replies[3].diagnostic.is_some());
+
+    // page 2 starts after the 25 replies of page 1
+    let floors = item.replies.iter().map(|x| x.floor).collect::<Vec<_>>();
+    assert_eq!(floors, vec![Some(26), Some(27), Some(28), Some(29)]);
+
+    assert_eq!(item.replies[0].reply_id, Some(String::from("9001")));
+    assert_eq!(item.replies[0].published_at.seconds, Some(1476870000));
+    assert_eq!(item.replies[1].published_at.seconds, Some(1476870060));
+    assert_eq!(item.replies[2].published_at.seconds, None);
+
+    // the thread names its author, so page 2 knows the OP too
+    assert_eq!(item.author_userid, Some(String::from("1001")));
+    assert!(item.replies[0].is_op);
+    assert!(!item.replies[1].is_op);
+}
+
+#[test]
+fn genders_are_mapped() {
+    let base_url = serve_fixtures();
+    let backend = JsonBackend::new(&base_url);
+    let url = backend.show_url("BW", "6500000", 2);
+
+    let (item, _) = backend.parse_show(fetch(&url).as_bytes(), &url).expect("fail to parse thread");
+
+    let genders = item.replies.iter().take(3).map(|x| x.gender).collect::<Vec<_>>();
+    assert_eq!(genders, vec![Gender::Male, Gender::Female, Gender::Unknown]);
+}
+
+#[test]
+fn links_point_at_the_forum() {
+    let base_url = serve_fixtures();
+    let backend = JsonBackend::new(&base_url);
+    let url = backend.show_url("BW", "6500000", 2);
+
+    let (item, _) = backend.parse_show(fetch(&url).as_bytes(), &url).expect("fail to parse thread");
+
+    let links = item.replies[0].body.iter().filter_map(|node| match *node {
+        NodeType::Link(ref n) => Some(n.url.clone()),
+        _ => None,
+    }).collect::<Vec<_>>();
+    assert_eq!(links, vec![String::from("http://forum1.hkgolden.com/view.aspx?message=6500001")]);
+}
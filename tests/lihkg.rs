@@ -0,0 +1,116 @@
+extern crate hkg;
+
+mod common;
+
+use hkg::backends::Backend;
+use hkg::backends::lihkg::LihkgBackend;
+use hkg::model::Gender;
+use hkg::reply_model::*;
+
+use common::fetch;
+
+fn serve_fixtures() -> String {
+    common::serve_fixtures(vec![("/api_v2/thread/category", "lihkg_category.json"),
+                                ("/api_v2/thread/100001/page/1", "lihkg_thread.json")])
+}
+
+#[test]
+fn topics_are_parsed_from_the_category_api() {
+    let base_url = serve_fixtures();
+    let backend = LihkgBackend::new(&base_url);
+    let url = backend.topics_url("1", 1);
+
+    let (topics, diagnostics) = backend.parse_topics(fetch(&url).as_bytes(), &url).expect("fail to parse topics");
+
+    assert_eq!(topics.len(), 2);
+    assert_eq!(diagnostics.len(), 1);
+
+    assert_eq!(topics[0].title.text, "今晚食咩好");
+    assert_eq!(topics[0].title.url_query.forum, "lihkg");
+    assert_eq!(topics[0].title.url_query.channel, "1");
+    assert_eq!(topics[0].title.url_query.message, "100001");
+    assert_eq!(topics[0].title.num_of_pages, 2);
+    assert_eq!(topics[0].author.name, "飯堂主任");
+    assert_eq!(topics[0].reply_count, Some(42));
+    assert_eq!(topics[0].rating, Some(7));
+    assert_eq!(topics[0].last_replied_at.seconds, Some(1500003000));
+
+    assert_eq!(topics[1].reply_count, Some(7));
+    assert_eq!(topics[1].rating, Some(1));
+}
+
+#[test]
+fn replies_are_parsed_from_the_thread_api() {
+    let base_url = serve_fixtures();
+    let backend = LihkgBackend::new(&base_url);
+    let url = backend.show_url("1", "100001", 1);
+
+    let (item, diagnostics) = backend.parse_show(fetch(&url).as_bytes(), &url).expect("fail to parse thread");
+
+    assert_eq!(item.url_query.forum, "lihkg");
+    assert_eq!(item.url_query.message, "100001");
+    assert_eq!(item.title, "今晚食咩好");
+    assert_eq!((item.page, item.max_page), (1, 2));
+    assert_eq!(item.reply_count, Some(42));
+
+    // the reply without a message stays as a placeholder
+    assert_eq!(item.replies.len(), 4);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(item.replies[3].diagnostic.is_some());
+
+    let floors = item.replies.iter().take(3).map(|x| x.floor).collect::<Vec<_>>();
+    assert_eq!(floors, vec![Some(1), Some(2), Some(3)]);
+    assert_eq!(item.replies[0].reply_id, Some(String::from("a1b2c3")));
+    assert_eq!(item.replies[0].published_at.seconds, Some(1500000000));
+
+    assert_eq!(item.replies[0].gender, Gender::Male);
+    assert_eq!(item.replies[1].gender, Gender::Female);
+
+    assert!(item.replies[0].is_op);
+    assert!(!item.replies[1].is_op);
+    assert!(item.replies[2].is_moderator);
+    assert!(!item.replies[0].is_moderator);
+}
+
+#[test]
+fn stickers_and_links_point_at_the_site() {
+    let base_url = serve_fixtures();
+    let backend = LihkgBackend::new(&base_url);
+    let url = backend.show_url("1", "100001", 1);
+
+    let (item, _) = backend.parse_show(fetch(&url).as_bytes(), &url).expect("fail to parse thread");
+
+    let images = item.replies[0].body.iter().filter_map(|node| match *node {
+        NodeType::Image(ref n) => Some((n.data.clone(), n.alt.clone())),
+        _ => None,
+    }).collect::<Vec<_>>();
+    assert_eq!(images, vec![(format!("{}/assets/faces/normal/smile.gif", base_url), String::from("#smile#"))]);
+
+    let links = item.replies[1].body.iter().filter_map(|node| match *node {
+        NodeType::Link(ref n) => Some(n.url.clone()),
+        _ => None,
+    }).collect::<Vec<_>>();
+    assert_eq!(links, vec![format!("{}/thread/100002/page/1", base_url)]);
+}
+
+#[test]
+fn pictures_are_downloaded_but_stickers_are_not() {
+    let base_url = serve_fixtures();
+    let backend = LihkgBackend::new(&base_url);
+    let url = backend.show_url("1", "100001", 1);
+
+    let (item, _) = backend.parse_show(fetch(&url).as_bytes(), &url).expect("fail to parse thread");
+
+    let images = |i: usize| item.replies[i].body.iter().filter_map(|node| match *node {
+        NodeType::Image(ref n) => Some(n.clone()),
+        _ => None,
+    }).collect::<Vec<_>>();
+
+    let sticker = images(0).remove(0);
+    assert!(!sticker.is_posted());
+
+    let picture = images(1).remove(0);
+    assert_eq!(picture.data, "https://i.imgur.com/abc.jpg");
+    assert_eq!(picture.alt, "[img]https://i.imgur.com/abc.jpg[/img]");
+    assert!(picture.is_posted());
+}
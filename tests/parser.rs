@@ -0,0 +1,76 @@
+extern crate hkg;
+extern crate kuchiki;
+
+use std::fs::File;
+use std::io::Read;
+
+use kuchiki::traits::*;
+
+use hkg::builders::common::PageBuilder;
+use hkg::model::ShowItem;
+use hkg::reply_model::*;
+
+const URL: &'static str = "http://forum1.hkgolden.com/view.aspx?type=BW&message=6500000&page=1";
+
+fn build_fixture(name: &str) -> ShowItem {
+    let mut html = String::new();
+    File::open(format!("tests/fixtures/{}", name)).and_then(|mut f| f.read_to_string(&mut html))
+                                                   .expect("fail to read fixture");
+    let document = kuchiki::parse_html().one(html.as_str());
+    hkg::builders::show::Show::new().build(&document, URL).expect("fail to build fixture")
+}
+
+fn lines(nodes: &Vec<NodeType>) -> Vec<String> {
+    let mut result = vec![String::new()];
+    for node in nodes {
+        match *node {
+            NodeType::Br(_) => result.push(String::new()),
+            _ => {
+                let last = result.len() - 1;
+                result[last].push_str(&text_contents(&vec![node.clone()]));
+            }
+        }
+    }
+    result
+}
+
+#[test]
+fn words_split_across_inline_elements_keep_their_space() {
+    let item = build_fixture("show_whitespace.html");
+    assert_eq!(item.replies.len(), 4);
+    assert_eq!(text_contents(&item.replies[0].body), "Hello world, foobar");
+}
+
+#[test]
+fn non_breaking_spaces_are_kept() {
+    let item = build_fixture("show_whitespace.html");
+    assert_eq!(lines(&item.replies[1].body),
+               vec!["\u{a0}\u{a0}/\\_/\\", "\u{a0}(\u{a0}o.o\u{a0})", "\u{a0}\u{a0}>\u{a0}^\u{a0}<"]);
+}
+
+#[test]
+fn pre_blocks_are_kept_verbatim() {
+    let item = build_fixture("show_whitespace.html");
+    let pres = item.replies[2].body.iter().filter_map(|node| match *node {
+        NodeType::Pre(ref n) => Some(n.data.clone()),
+        _ => None,
+    }).collect::<Vec<_>>();
+
+    assert_eq!(pres, vec!["fn main() {\n    println!(\"hi\");\n}", "  a   b\n    c"]);
+}
+
+#[test]
+fn runs_of_whitespace_collapse_to_one_space() {
+    let item = build_fixture("show_whitespace.html");
+    assert_eq!(lines(&item.replies[3].body), vec!["multiple spaces and lines", "next"]);
+}
+
+#[test]
+fn the_floor_is_read_next_to_the_date_not_from_the_reply() {
+    let item = build_fixture("show_floor.html");
+    assert_eq!(item.replies.len(), 2);
+
+    assert_eq!(item.replies[0].floor, Some(1));
+    // "#1" written in the reply is not its floor
+    assert_eq!(item.replies[1].floor, None);
+}
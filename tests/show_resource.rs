@@ -0,0 +1,58 @@
+extern crate hkg;
+
+mod common;
+
+use hkg::backends::json::JsonBackend;
+use hkg::caches::keys::show_key;
+use hkg::model::ShowItem;
+use hkg::resources::*;
+use hkg::resources::common::Resource;
+use hkg::resources::show_resource::ShowResource;
+use hkg::resources::web_resource::WebResource;
+
+use common::TestCache;
+
+const POSTID: &'static str = "6600000";
+
+fn show_page(cache: &mut TestCache, page: usize) -> Option<ShowItem> {
+    let backend = JsonBackend::new("http://127.0.0.1:1");
+    let mut wr = WebResource::new();
+    let request = ChannelItem {
+        extra: Some(ChannelItemType::Show(ChannelShowItem {
+            postid: String::from(POSTID),
+            page: page,
+            ..Default::default()
+        })),
+        result: Default::default(),
+        unavailable: false,
+    };
+
+    // offline, so only the cache is read
+    let response = ShowResource::new(&mut wr, cache, &backend, "BW", true).fetch(&request);
+    match response.extra {
+        Some(ChannelItemType::Show(extra)) => extra.show_item,
+        _ => None,
+    }
+}
+
+#[test]
+fn a_later_page_takes_the_author_from_page_1() {
+    let mut cache = TestCache::default().with_fixture(&show_key(POSTID, 1, "json"), "hkgolden_thread_page1.json")
+                                        .with_fixture(&show_key(POSTID, 2, "json"), "hkgolden_thread_page2.json");
+
+    let item = show_page(&mut cache, 2).expect("fail to read page 2");
+
+    assert_eq!(item.author_userid, Some(String::from("2001")));
+    assert!(!item.replies[0].is_op);
+    assert!(item.replies[1].is_op);
+}
+
+#[test]
+fn a_later_page_has_no_author_without_page_1() {
+    let mut cache = TestCache::default().with_fixture(&show_key(POSTID, 2, "json"), "hkgolden_thread_page2.json");
+
+    let item = show_page(&mut cache, 2).expect("fail to read page 2");
+
+    assert_eq!(item.author_userid, None);
+    assert!(item.replies.iter().all(|x| !x.is_op));
+}
@@ -0,0 +1,63 @@
+extern crate hkg;
+
+mod common;
+
+use hkg::caches::shared_cache::SharedCache;
+use hkg::icons::IconRegistry;
+use hkg::model::{ShowItem, ShowReplyItem};
+use hkg::reply_model::*;
+use hkg::screen::show::Show;
+use hkg::utility::time::DisplayZone;
+
+use common::TestCache;
+
+// 10 rows, 7 of them for the body
+fn screen() -> Show {
+    Show::new(IconRegistry::new(), false, DisplayZone::Local, SharedCache::new(Box::new(TestCache::default()))).with_size(40, 10)
+}
+
+fn filtered_item(replies: usize) -> ShowItem {
+    ShowItem {
+        title: String::from("連載小說"),
+        page: 1,
+        max_page: 1,
+        replies: (0..replies).map(|i| ShowReplyItem {
+            userid: String::from("2001"),
+            username: String::from("樓主"),
+            body: vec![NodeType::Text(TextNode { data: format!("reply {}", i) })],
+            reply_id: Some(i.to_string()),
+            ..Default::default()
+        }).collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn replies_beyond_the_body_height_can_be_scrolled_to() {
+    let mut show = screen();
+    show.set_filter(Some(String::from("2001")));
+    let item = filtered_item(20);
+    let mut out = Vec::new();
+
+    show.print(&mut out, &item);
+    while show.scroll_down(1) {
+        show.print(&mut out, &item);
+    }
+
+    // the last reply is in the body, the one before it may still be at the top
+    assert!(show.current_reply() >= 18);
+    assert!(String::from_utf8_lossy(&out).contains("reply 19"));
+}
+
+#[test]
+fn only_replies_within_the_body_are_written() {
+    let mut show = screen();
+    let item = filtered_item(20);
+    let mut out = Vec::new();
+
+    show.print(&mut out, &item);
+
+    let out = String::from_utf8_lossy(&out);
+    assert!(out.contains("reply 0"));
+    assert!(!out.contains("reply 19"));
+}